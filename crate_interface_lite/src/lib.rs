@@ -4,38 +4,331 @@
 /// Define an interface.
 ///
 /// This attribute should be used with a `trait` item. All traits that use the
-/// attribute cannot have the same name.
+/// attribute cannot have the same name, unless they are assigned different
+/// namespaces with the `namespace = Ident,` prefix.
 ///
 /// It is not necessary to define it in the same crate as the implementation,
 /// but it is required that these crates are linked together.
 ///
 /// See the [crate-level documentation](crate) for more details.
+///
+/// # Calling Helper Functions
+///
+/// With the `gen_caller,` prefix, a calling helper function with the same
+/// visibility as the trait is generated for each interface function.
 #[macro_export]
 macro_rules! def_interface {
-    ($(#[$attr:meta])* $vis:vis trait $name:ident {$(
-        $(#[$fn_attr:meta])*
-        fn $fn_name:ident($($arg_name:ident: $arg_ty:ty),* $(,)?) $(-> $ret_ty:ty)?;
-    )*}) => {
+    ($($item:tt)*) => {
+        $crate::__def_interface!(@args [] [] $($item)*);
+    };
+}
+
+/// The implementation of [`def_interface!`], with the optional namespace and
+/// the optional `gen_caller` flag in brackets.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __def_interface {
+    (@args [] $gen:tt namespace = $ns:ident, $($item:tt)*) => {
+        $crate::__def_interface!(@args [$ns] $gen $($item)*);
+    };
+    (@args $ns:tt [] gen_caller, $($item:tt)*) => {
+        $crate::__def_interface!(@args $ns [gen_caller] $($item)*);
+    };
+    (@args $ns:tt $gen:tt $($item:tt)*) => {
+        $crate::__def_interface!($ns $gen $($item)*);
+    };
+    ($ns:tt $gen:tt $(#[$attr:meta])* $vis:vis trait $name:ident { $($body:tt)* }) => {
+        $crate::__munch_fns!(__def_interface [$ns $gen [$(#[$attr])*] $vis $name] [] $($body)*);
+    };
+    (@fns [$ns:tt $gen:tt [$(#[$attr:meta])*] $vis:vis $name:ident] [$($method:tt)*]) => {
         $(#[$attr])*
         $vis trait $name {
             #[doc(hidden)]
-            #[allow(non_upper_case_globals)]
-            const $name: $crate::r#priv::MustNotAnAlias = $crate::r#priv::MustNotAnAlias;
+            const __InterfaceName: &'static str = stringify!($name);
+
+            #[doc(hidden)]
+            const __InterfaceNamespace: &'static str = $crate::__namespace_guard!($ns);
+
+            $($crate::__trait_fn!($name $method);)*
+        }
 
-            $($(#[$fn_attr])*
-            fn $fn_name($($arg_name: $arg_ty,)*) $(-> $ret_ty)?;)*
+        impl $name for $crate::r#priv::DefaultImpl {
+            $($crate::__extern_fn!([$ns $name] $method);)*
         }
 
-        impl $name for $crate::r#priv::DefaultImpl {$(
-            $(#[$fn_attr])*
-            fn $fn_name($($arg_name: $arg_ty,)*) $(-> $ret_ty)? {
-                extern "Rust" {
-                    #[link_name = concat!("__", stringify!($name), "__", stringify!($fn_name))]
-                    fn $fn_name($($arg_name: $arg_ty,)*) $(-> $ret_ty)?;
-                }
-                unsafe { $fn_name($($arg_name,)*) }
+        $crate::__weak_defaults!([$ns $name] [$($method)*] [$($method)*]);
+
+        $($crate::__caller!($gen $name $vis, $method);)*
+    };
+}
+
+/// Normalizes the methods of an interface definition or implementation, and
+/// passes them to the `@fns` rule of the given macro, with the given header.
+///
+/// Each method is normalized to a braced group of its attributes, the optional
+/// `unsafe` qualifier, the optional ABI, the name, the lifetime parameters, the
+/// arguments, the optional return type and the optional body, each of which
+/// (except for the name) is in brackets. Each argument is normalized to a braced
+/// group of its attributes, its binding (`name` or `mut name`) in brackets and
+/// its type.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __munch_fns {
+    ($cb:ident $hdr:tt [$($done:tt)*]) => {
+        $crate::$cb!(@fns $hdr [$($done)*]);
+    };
+    ($cb:ident $hdr:tt [$($done:tt)*]
+        $(#[$fn_attr:meta])*
+        unsafe $(extern $abi:literal)? fn $fn_name:ident $(<$($lt:lifetime),* $(,)?>)?
+        ($($(#[$arg_attr:meta])* $arg:ident $($arg_ident:ident)?: $arg_ty:ty),* $(,)?)
+        $(-> $ret_ty:ty)? ;
+        $($rest:tt)*
+    ) => {
+        $crate::__munch_fns!($cb $hdr [$($done)* {
+            [$(#[$fn_attr])*] [unsafe] [$($abi)?] $fn_name [$($($lt),*)?]
+            [$({ [$(#[$arg_attr])*] [$arg $($arg_ident)?] $arg_ty })*] [$($ret_ty)?] []
+        }] $($rest)*);
+    };
+    ($cb:ident $hdr:tt [$($done:tt)*]
+        $(#[$fn_attr:meta])*
+        unsafe $(extern $abi:literal)? fn $fn_name:ident $(<$($lt:lifetime),* $(,)?>)?
+        ($($(#[$arg_attr:meta])* $arg:ident $($arg_ident:ident)?: $arg_ty:ty),* $(,)?)
+        $(-> $ret_ty:ty)? $default:block
+        $($rest:tt)*
+    ) => {
+        $crate::__munch_fns!($cb $hdr [$($done)* {
+            [$(#[$fn_attr])*] [unsafe] [$($abi)?] $fn_name [$($($lt),*)?]
+            [$({ [$(#[$arg_attr])*] [$arg $($arg_ident)?] $arg_ty })*] [$($ret_ty)?] [$default]
+        }] $($rest)*);
+    };
+    ($cb:ident $hdr:tt [$($done:tt)*]
+        $(#[$fn_attr:meta])*
+        $(extern $abi:literal)? fn $fn_name:ident $(<$($lt:lifetime),* $(,)?>)?
+        ($($(#[$arg_attr:meta])* $arg:ident $($arg_ident:ident)?: $arg_ty:ty),* $(,)?)
+        $(-> $ret_ty:ty)? ;
+        $($rest:tt)*
+    ) => {
+        $crate::__munch_fns!($cb $hdr [$($done)* {
+            [$(#[$fn_attr])*] [] [$($abi)?] $fn_name [$($($lt),*)?]
+            [$({ [$(#[$arg_attr])*] [$arg $($arg_ident)?] $arg_ty })*] [$($ret_ty)?] []
+        }] $($rest)*);
+    };
+    ($cb:ident $hdr:tt [$($done:tt)*]
+        $(#[$fn_attr:meta])*
+        $(extern $abi:literal)? fn $fn_name:ident $(<$($lt:lifetime),* $(,)?>)?
+        ($($(#[$arg_attr:meta])* $arg:ident $($arg_ident:ident)?: $arg_ty:ty),* $(,)?)
+        $(-> $ret_ty:ty)? $default:block
+        $($rest:tt)*
+    ) => {
+        $crate::__munch_fns!($cb $hdr [$($done)* {
+            [$(#[$fn_attr])*] [] [$($abi)?] $fn_name [$($($lt),*)?]
+            [$({ [$(#[$arg_attr])*] [$arg $($arg_ident)?] $arg_ty })*] [$($ret_ty)?] [$default]
+        }] $($rest)*);
+    };
+}
+
+/// Converts an argument binding (`name` or `mut name`) to the argument name.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __arg_name {
+    (mut $arg:ident) => {
+        $arg
+    };
+    ($arg:ident) => {
+        $arg
+    };
+}
+
+/// Calls the given function, in an `unsafe` block if `unsafe` is in brackets.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __call {
+    ([] $($call:tt)*) => {
+        $($call)*
+    };
+    ([unsafe] $($call:tt)*) => {
+        unsafe { $($call)* }
+    };
+}
+
+/// Generates a method of an interface trait, which forwards to the default
+/// interface implementation if it has a default body.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __trait_fn {
+    ($name:ident {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] []
+    }) => {
+        $(#[$fn_attr])*
+        $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+            $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+        ) $(-> $ret_ty)?;
+    };
+    ($name:ident {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] [$default:block]
+    }) => {
+        $(#[$fn_attr])*
+        $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+            $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+        ) $(-> $ret_ty)? {
+            $crate::__weak_default_required!($fn_name);
+            $crate::__call!([$($unsafe)?] <$crate::r#priv::DefaultImpl as $name>::$fn_name(
+                $($crate::__arg_name!($($arg)+)),*
+            ))
+        }
+    };
+}
+
+/// Generates a method of the default interface implementation, which calls the
+/// extern function of the interface.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __extern_fn {
+    ($hdr:tt { $attrs:tt $unsafe:tt [] $($method:tt)* }) => {
+        $crate::__extern_fn!($hdr { $attrs $unsafe ["Rust"] $($method)* });
+    };
+    ([$ns:tt $name:ident] {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] $default:tt
+    }) => {
+        $(#[$fn_attr])*
+        $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+            $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+        ) $(-> $ret_ty)? {
+            $(extern $abi)? {
+                #[link_name = $crate::__symbol_name!($ns $name $fn_name)]
+                fn $fn_name<$($lt),*>(
+                    $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+                ) $(-> $ret_ty)?;
+            }
+            unsafe { $fn_name($($crate::__arg_name!($($arg)+)),*) }
+        }
+    };
+}
+
+/// Emits a compile error if the `weak_default` feature is not enabled.
+#[cfg(not(feature = "weak_default"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __weak_default_required {
+    ($fn_name:ident) => {
+        compile_error!(concat!(
+            "default implementation of method `",
+            stringify!($fn_name),
+            "` is not allowed without the `weak_default` feature of crate_interface_lite",
+        ));
+    };
+}
+
+#[cfg(feature = "weak_default")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __weak_default_required {
+    ($fn_name:ident) => {};
+}
+
+/// Generates the weak symbols for the methods with default bodies, given all
+/// methods of the interface twice.
+#[cfg(feature = "weak_default")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __weak_defaults {
+    ($hdr:tt $all:tt [$($method:tt)*]) => {
+        $($crate::__weak_default!($hdr $all $method);)*
+    };
+}
+
+#[cfg(not(feature = "weak_default"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __weak_defaults {
+    ($($tt:tt)*) => {};
+}
+
+/// Generates the weak symbol for a method, if it has a default body.
+///
+/// The default body is placed in a local implementor of the interface, whose
+/// methods forward to the default interface implementation, so that `Self::`
+/// calls in the body are dispatched to the linked implementation.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __weak_default {
+    ($hdr:tt $all:tt { $attrs:tt $unsafe:tt $abi:tt $fn_name:ident $lts:tt $args:tt $ret:tt [] }) => {};
+    ($hdr:tt $all:tt { $attrs:tt $unsafe:tt [] $($method:tt)* }) => {
+        $crate::__weak_default!($hdr $all { $attrs $unsafe ["Rust"] $($method)* });
+    };
+    ([$ns:tt $name:ident] [$($all:tt)*] {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] [$default:block]
+    }) => {
+        const _: () = {
+            struct __WeakDefault;
+
+            impl $name for __WeakDefault {
+                $($crate::__forward_fn!($name $all);)*
             }
-        )*}
+
+            impl __WeakDefault {
+                #[inline]
+                $($unsafe)? fn $fn_name<$($lt),*>(
+                    $($(#[$arg_attr])* $($arg)+: $arg_ty),*
+                ) $(-> $ret_ty)? $default
+            }
+
+            #[linkage = "weak"]
+            #[export_name = $crate::__symbol_name!($ns $name $fn_name)]
+            $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+                $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+            ) $(-> $ret_ty)? {
+                $crate::__call!([$($unsafe)?] __WeakDefault::$fn_name(
+                    $($crate::__arg_name!($($arg)+)),*
+                ))
+            }
+        };
+    };
+}
+
+/// Generates a method that forwards to the default interface implementation.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forward_fn {
+    ($name:ident {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] $default:tt
+    }) => {
+        $(#[$fn_attr])*
+        #[inline]
+        $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+            $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+        ) $(-> $ret_ty)? {
+            $crate::__call!([$($unsafe)?] <$crate::r#priv::DefaultImpl as $name>::$fn_name(
+                $($crate::__arg_name!($($arg)+)),*
+            ))
+        }
+    };
+}
+
+/// Generates the calling helper function of an interface function, if the
+/// `gen_caller` flag in brackets is set.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __caller {
+    ([] $($item:tt)*) => {};
+    ([gen_caller] $name:ident $vis:vis, {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] $default:tt
+    }) => {
+        $(#[$fn_attr])*
+        #[inline]
+        $vis $($unsafe)? fn $fn_name<$($lt),*>(
+            $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+        ) $(-> $ret_ty)? {
+            $crate::__call!([$($unsafe)?] <$crate::r#priv::DefaultImpl as $name>::$fn_name(
+                $($crate::__arg_name!($($arg)+)),*
+            ))
+        }
     };
 }
 
@@ -51,6 +344,27 @@ macro_rules! def_interface {
 ///
 /// # Caveat
 ///
+/// The namespace given with the `namespace = Ident,` prefix must match the one
+/// used to define the interface:
+///
+/// ```rust,compile_fail
+/// # use crate_interface_lite::*;
+/// def_interface!(
+///     namespace = MyNs,
+///     trait MyIf {
+///         fn foo();
+///     }
+/// );
+///
+/// struct MyImpl;
+/// impl_interface!(
+///     namespace = OtherNs, // error: namespace does not match
+///     impl MyIf for MyImpl {
+///         fn foo() {}
+///     }
+/// );
+/// ```
+///
 /// The specified trait name must not be an alias to the originally defined
 /// name; otherwise, it will result in a compile error.
 ///
@@ -73,22 +387,59 @@ macro_rules! def_interface {
 /// ```
 #[macro_export]
 macro_rules! impl_interface {
-    ($(#[$attr:meta])* impl $interface:ident for $target:ident {$(
-        $(#[$fn_attr:meta])*
-        fn $fn_name:ident($($arg_name:ident: $arg_ty:ty),* $(,)?) $(-> $ret_ty:ty)? { $($body:tt)* }
-    )*}) => {
+    (namespace = $ns:ident, $($item:tt)*) => {
+        $crate::__impl_interface!([$ns] $($item)*);
+    };
+    ($($item:tt)*) => {
+        $crate::__impl_interface!([] $($item)*);
+    };
+}
+
+/// The implementation of [`impl_interface!`], with the optional namespace in
+/// brackets.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __impl_interface {
+    ($ns:tt $(#[$attr:meta])* impl $interface:ident for $target:ident { $($body:tt)* }) => {
+        $crate::__munch_fns!(__impl_interface [$ns [$(#[$attr])*] $interface $target] [] $($body)*);
+    };
+    (@fns [$ns:tt [$(#[$attr:meta])*] $interface:ident $target:ident] [$($method:tt)*]) => {
         $(#[$attr])*
         impl $interface for $target {
-            const $interface: $crate::r#priv::MustNotAnAlias = $crate::r#priv::MustNotAnAlias;
-
-            $($(#[$fn_attr])*
-            fn $fn_name($($arg_name: $arg_ty,)*) $(-> $ret_ty)? {
-                #[export_name = concat!("__", stringify!($interface), "__", stringify!($fn_name))]
-                extern "Rust" fn $fn_name($($arg_name: $arg_ty,)*) $(-> $ret_ty)? {
-                    $($body)*
-                }
-                $fn_name($($arg_name,)*)
-            })*
+            $($crate::__export_fn!([$ns $interface] $method);)*
+        }
+
+        const _: () = {
+            $crate::r#priv::check_name(
+                <$target as $interface>::__InterfaceName,
+                stringify!($interface),
+            );
+            $crate::r#priv::check_namespace(
+                <$target as $interface>::__InterfaceNamespace,
+                $crate::__namespace_guard!($ns),
+            );
+        };
+    };
+}
+
+/// Generates a method of an interface implementation, which exports its body as
+/// the extern function of the interface.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __export_fn {
+    ([$ns:tt $interface:ident] {
+        [$(#[$fn_attr:meta])*] [$($unsafe:tt)?] [$($abi:literal)?] $fn_name:ident [$($lt:lifetime),*]
+        [$({ [$(#[$arg_attr:meta])*] [$($arg:tt)+] $arg_ty:ty })*] [$($ret_ty:ty)?] [$default:block]
+    }) => {
+        $(#[$fn_attr])*
+        $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+            $($(#[$arg_attr])* $crate::__arg_name!($($arg)+): $arg_ty),*
+        ) $(-> $ret_ty)? {
+            #[export_name = $crate::__symbol_name!($ns $interface $fn_name)]
+            $($unsafe)? $(extern $abi)? fn $fn_name<$($lt),*>(
+                $($(#[$arg_attr])* $($arg)+: $arg_ty),*
+            ) $(-> $ret_ty)? $default
+            $crate::__call!([$($unsafe)?] $fn_name($($crate::__arg_name!($($arg)+)),*))
         }
     };
 }
@@ -99,8 +450,27 @@ macro_rules! impl_interface {
 /// it is required that these crates are linked together.
 ///
 /// See the [crate-level documentation](crate) for more details.
+///
+/// If the interface is defined with a namespace, the same namespace should be
+/// given with the `namespace = Ident,` prefix.
 #[macro_export]
 macro_rules! call_interface {
+    (namespace = $ns:ident, $($path:ident)::+ $(, $args:expr)* $(,)?) => {{
+        $crate::__namespace_guard!(@check $ns [] $($path)::*);
+        ($crate::__interface_fn!([] $($path)::*))($($args,)*)
+    }};
+    (namespace = $ns:ident, $($path:ident)::+ ($($args:tt)*) $(,)?) => {{
+        $crate::__namespace_guard!(@check $ns [] $($path)::*);
+        ($crate::__interface_fn!([] $($path)::*))($($args)*)
+    }};
+    (namespace = $ns:ident, ::$($path:ident)::+ $(, $args:expr)* $(,)?) => {{
+        $crate::__namespace_guard!(@check $ns [::] $($path)::*);
+        ($crate::__interface_fn!([::] $($path)::*))($($args,)*)
+    }};
+    (namespace = $ns:ident, ::$($path:ident)::+ ($($args:tt)*) $(,)?) => {{
+        $crate::__namespace_guard!(@check $ns [::] $($path)::*);
+        ($crate::__interface_fn!([::] $($path)::*))($($args)*)
+    }};
     ($($path:ident)::+ $(, $args:expr)* $(,)?) => {
         ($crate::__interface_fn!([] $($path)::*))($($args,)*)
     };
@@ -127,12 +497,89 @@ macro_rules! __interface_fn {
     };
 }
 
+/// Generates the value of the namespace guard of an interface (the namespace,
+/// or an empty string without one), or checks it against the namespace given
+/// to a call.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __namespace_guard {
+    ([]) => {
+        ""
+    };
+    ([$ns:ident]) => {
+        stringify!($ns)
+    };
+    (@check $ns:ident [$($path:tt)*] $interface:ident::$fn_name:ident) => {
+        const _: () = $crate::r#priv::check_namespace(
+            <$crate::r#priv::DefaultImpl as $($path)*$interface>::__InterfaceNamespace,
+            stringify!($ns),
+        );
+    };
+    (@check $ns:ident [$($path:tt)*] $head:ident::$($rest:tt)+) => {
+        $crate::__namespace_guard!(@check $ns [$($path)* $head::] $($rest)*)
+    };
+}
+
+/// Generates the symbol name of an interface function, with the optional
+/// namespace in brackets.
+///
+/// The symbols are mangled exactly like those of crate_interface, namely
+/// `__Trait_fn` or `__Namespace_Trait_fn`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __symbol_name {
+    ([] $interface:ident $fn_name:ident) => {
+        concat!("__", stringify!($interface), "_", stringify!($fn_name))
+    };
+    ([$ns:ident] $interface:ident $fn_name:ident) => {
+        concat!(
+            "__",
+            stringify!($ns),
+            "_",
+            stringify!($interface),
+            "_",
+            stringify!($fn_name)
+        )
+    };
+}
+
 /// NON-PUBLIC APIs
 #[doc(hidden)]
 pub mod r#priv {
     /// The default implementor for all defined interfaces.
     pub struct DefaultImpl;
 
-    /// A dummy type to enforce no trait aliasing.
-    pub struct MustNotAnAlias;
+    /// Checks the name guard of an interface against the name it is
+    /// implemented with, which differ if it is implemented through an alias.
+    ///
+    /// The guards are associated constants with fixed names, shared with
+    /// crate_interface, as identifiers can not be concatenated here.
+    pub const fn check_name(name: &str, implemented: &str) {
+        if !str_eq(name, implemented) {
+            panic!("the interface must be implemented by its name, not through an alias");
+        }
+    }
+
+    /// Checks the namespace guard of an interface against the namespace it is
+    /// implemented or called with.
+    pub const fn check_namespace(namespace: &str, given: &str) {
+        if !str_eq(namespace, given) {
+            panic!("the namespace does not match the definition of the interface");
+        }
+    }
+
+    const fn str_eq(a: &str, b: &str) -> bool {
+        let (a, b) = (a.as_bytes(), b.as_bytes());
+        if a.len() != b.len() {
+            return false;
+        }
+        let mut i = 0;
+        while i < a.len() {
+            if a[i] != b[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
 }
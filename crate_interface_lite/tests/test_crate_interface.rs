@@ -24,6 +24,109 @@ impl_interface!(
     }
 );
 
+def_interface!(
+    gen_caller,
+    trait WithCallerIf {
+        /// Test comments
+        fn baz(x: i32) -> i32;
+    }
+);
+
+pub struct WithCallerIfImpl;
+impl_interface!(
+    impl WithCallerIf for WithCallerIfImpl {
+        fn baz(x: i32) -> i32 {
+            x + 1
+        }
+    }
+);
+
+mod a {
+    crate_interface_lite::def_interface!(
+        gen_caller,
+        namespace = A_NS,
+        pub trait NamespaceIf {
+            fn qux() -> i32;
+        }
+    );
+}
+
+mod b {
+    crate_interface_lite::def_interface!(
+        namespace = B_NS,
+        gen_caller,
+        pub trait NamespaceIf {
+            fn qux() -> i32;
+        }
+    );
+}
+
+pub struct NamespaceIfImplA;
+pub struct NamespaceIfImplB;
+
+mod impls {
+    use super::{a::NamespaceIf, NamespaceIfImplA};
+
+    crate_interface_lite::impl_interface!(
+        namespace = A_NS,
+        impl NamespaceIf for NamespaceIfImplA {
+            fn qux() -> i32 {
+                1
+            }
+        }
+    );
+}
+
+use b::NamespaceIf;
+impl_interface!(
+    namespace = B_NS,
+    impl NamespaceIf for NamespaceIfImplB {
+        fn qux() -> i32 {
+            2
+        }
+    }
+);
+
+def_interface!(
+    gen_caller,
+    trait SignatureIf {
+        unsafe fn read_byte(ptr: *const u8) -> u8;
+        extern "C" fn c_add(a: u32, b: u32) -> u32;
+        fn longer<'a>(a: &'a str, b: &'a str) -> &'a str;
+        fn count_down(mut n: u32, #[allow(unused_variables)] _unused: u32) -> u32;
+    }
+);
+
+pub struct SignatureIfImpl;
+impl_interface!(
+    impl SignatureIf for SignatureIfImpl {
+        unsafe fn read_byte(ptr: *const u8) -> u8 {
+            *ptr
+        }
+
+        extern "C" fn c_add(a: u32, b: u32) -> u32 {
+            a + b
+        }
+
+        fn longer<'a>(a: &'a str, b: &'a str) -> &'a str {
+            if a.len() >= b.len() {
+                a
+            } else {
+                b
+            }
+        }
+
+        fn count_down(mut n: u32, #[allow(unused_variables)] unused: u32) -> u32 {
+            let mut steps = 0;
+            while n > 0 {
+                n -= 1;
+                steps += 1;
+            }
+            steps
+        }
+    }
+);
+
 mod private {
     pub fn test_call_in_mod() {
         crate::call_interface!(super::SimpleIf::bar(123, &[2, 3, 5, 7, 11], "test"));
@@ -37,3 +140,40 @@ fn test_crate_interface_call() {
     assert_eq!(call_interface!(SimpleIf::foo), 456);
     private::test_call_in_mod();
 }
+
+#[test]
+fn test_calling_helper_function() {
+    assert_eq!(baz(42), 43);
+}
+
+#[test]
+fn test_namespace_interface() {
+    assert_eq!(call_interface!(namespace = A_NS, a::NamespaceIf::qux), 1);
+    assert_eq!(call_interface!(namespace = B_NS, b::NamespaceIf::qux()), 2);
+    assert_eq!(
+        call_interface!(namespace = B_NS, crate::b::NamespaceIf::qux),
+        2
+    );
+
+    assert_eq!(a::qux(), 1);
+    assert_eq!(b::qux(), 2);
+}
+
+#[test]
+fn test_signature_grammar() {
+    let byte = 42u8;
+    assert_eq!(
+        unsafe { call_interface!(SignatureIf::read_byte(&byte)) },
+        42
+    );
+    assert_eq!(unsafe { read_byte(&byte) }, 42);
+    assert_eq!(call_interface!(SignatureIf::c_add(1, 2)), 3);
+    assert_eq!(c_add(3, 4), 7);
+    assert_eq!(
+        call_interface!(SignatureIf::longer("hello", "world!")),
+        "world!"
+    );
+    assert_eq!(longer("foo", "ba"), "foo");
+    assert_eq!(call_interface!(SignatureIf::count_down(3, 0)), 3);
+    assert_eq!(count_down(5, 0), 5);
+}
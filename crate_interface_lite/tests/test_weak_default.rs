@@ -0,0 +1,67 @@
+#![cfg(feature = "weak_default")]
+#![cfg_attr(feature = "weak_default", feature(linkage))]
+
+//! Test weak_default feature.
+//!
+//! This test requires nightly Rust and the `weak_default` feature to be enabled.
+//! Run with: cargo +nightly test --features weak_default --test test_weak_default
+
+use crate_interface_lite::*;
+
+def_interface!(
+    trait DefaultMethodIf {
+        /// Method with default implementation - implementor may skip this.
+        fn default_method() -> u32 {
+            42
+        }
+
+        /// Method with default implementation that takes arguments.
+        fn default_with_args(a: u32, b: u32) -> u32 {
+            a + b
+        }
+
+        /// Method with default implementation that calls other methods.
+        fn default_with_self_calls() -> u32 {
+            Self::required_method() + Self::default_method()
+        }
+
+        /// Unsafe method with default implementation, lifetimes and `mut` bindings.
+        unsafe fn default_unsafe<'a>(mut skip: usize, s: &'a str, _t: &'a str) -> &'a str {
+            while skip > 0 && !s.is_empty() {
+                skip -= 1;
+            }
+            &s[Self::required_method() as usize / 100..]
+        }
+
+        /// Method without default implementation - must be implemented.
+        fn required_method() -> u32;
+    }
+);
+
+pub struct PartialImpl;
+
+impl_interface!(
+    impl DefaultMethodIf for PartialImpl {
+        fn required_method() -> u32 {
+            100
+        }
+    }
+);
+
+#[test]
+fn test_weak_default_methods() {
+    assert_eq!(call_interface!(DefaultMethodIf::required_method), 100);
+    assert_eq!(call_interface!(DefaultMethodIf::default_method), 42);
+    assert_eq!(
+        call_interface!(DefaultMethodIf::default_with_args, 10, 20),
+        30
+    );
+    assert_eq!(
+        call_interface!(DefaultMethodIf::default_with_self_calls()),
+        142
+    );
+    assert_eq!(
+        unsafe { call_interface!(DefaultMethodIf::default_unsafe(1, "abc", "")) },
+        "bc"
+    );
+}
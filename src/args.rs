@@ -1,26 +1,248 @@
 //! Arguments definition and parsing for the `def_interface`, `impl_interface`
 //! attributes and the `call_interface!` macro.
 
+use proc_macro2::TokenStream;
+use quote::quote;
 use syn::{
     parenthesized,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
-    Expr, Ident, Path, Result, Token,
+    Attribute, Error, Expr, Ident, ItemTrait, LitInt, LitStr, Path, Result, Token, Type,
+    Visibility,
 };
 
 use crate::errors::{duplicate_arg_error, unknown_arg_error};
 
+const KEY_BOUND: &str = "bound";
+const KEY_CHECKED: &str = "checked";
+const KEY_DEFINITION: &str = "__definition";
+const KEY_DYLIB: &str = "dylib";
+const KEY_EXPORT_DYNAMIC: &str = "export_dynamic";
 const KEY_GEN_CALLER: &str = "gen_caller";
+const KEY_INSTANCE: &str = "instance";
+const KEY_LINK_SECTION: &str = "link_section";
+const KEY_LOWER_IMPL_TRAIT: &str = "lower_impl_trait";
 const KEY_NAMESPACE: &str = "namespace";
+const KEY_SELECT: &str = "select";
+const KEY_STATS: &str = "stats";
+const KEY_TRACE: &str = "trace";
+const KEY_TYPES: &str = "types";
+const KEY_UNWIND: &str = "unwind";
+const KEY_VALUE: &str = "value";
+const KEY_VERSION: &str = "version";
+const KEY_VISIBILITY: &str = "visibility";
+
+/// Parse the value of the `version` argument, which must be a positive integer.
+fn parse_version(input: ParseStream) -> Result<u32> {
+    input.parse::<Token![=]>()?;
+    let lit: LitInt = input.parse()?;
+    let version = lit.base10_parse::<u32>()?;
+    if version == 0 {
+        return Err(Error::new_spanned(lit, "interface versions start from 1"));
+    }
+    Ok(version)
+}
+
+/// Parse the value of the `types` argument, which binds associated types to
+/// concrete types like `(Error = MyError, Word = u64)`.
+fn parse_types(input: ParseStream) -> Result<Vec<(Ident, Type)>> {
+    let content;
+    parenthesized!(content in input);
+
+    let mut types: Vec<(Ident, Type)> = vec![];
+    while !content.is_empty() {
+        let name: Ident = content.parse()?;
+        if types.iter().any(|(other, _)| *other == name) {
+            return Err(Error::new_spanned(
+                &name,
+                format!("duplicate binding of associated type `{name}`"),
+            ));
+        }
+        content.parse::<Token![=]>()?;
+        types.push((name, content.parse()?));
+
+        if !content.is_empty() {
+            content.parse::<Token![,]>()?;
+        }
+    }
+    Ok(types)
+}
+
+/// What happens when the implementation of an interface function panics, given
+/// by the `unwind` argument.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum UnwindPolicy {
+    /// Abort the process instead of unwinding out of the function.
+    Abort,
+    /// Unwind into the caller, which is the behavior without the argument.
+    Propagate,
+    /// Catch the panic, and return it as an error from the methods returning a
+    /// `Result`, or pass it to the panic hook otherwise.
+    Catch,
+}
+
+impl UnwindPolicy {
+    /// Returns the name of the policy.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UnwindPolicy::Abort => "abort",
+            UnwindPolicy::Propagate => "propagate",
+            UnwindPolicy::Catch => "catch",
+        }
+    }
+}
+
+/// Parse the value of the `unwind` argument.
+fn parse_unwind(input: ParseStream) -> Result<UnwindPolicy> {
+    input.parse::<Token![=]>()?;
+    let policy: Ident = input.parse()?;
+    match policy.to_string().as_str() {
+        "abort" => Ok(UnwindPolicy::Abort),
+        "propagate" => Ok(UnwindPolicy::Propagate),
+        "catch" => Ok(UnwindPolicy::Catch),
+        _ => Err(Error::new_spanned(
+            policy,
+            "expect `abort`, `propagate` or `catch`",
+        )),
+    }
+}
+
+/// Placement of the functions exported for an interface in the linked binary,
+/// given by the `link_section` and `visibility` arguments, for the whole
+/// interface or per method.
+#[derive(Clone, Default)]
+pub struct SymbolPlacement {
+    /// The linker section the functions are placed in.
+    pub link_section: Option<LitStr>,
+    /// The ELF visibility of the symbols, `hidden`, `protected` or `default`.
+    pub visibility: Option<Ident>,
+}
+
+impl SymbolPlacement {
+    /// Parse the placement argument named `ident`, followed by `= value` in
+    /// `input`. Returns `false` if `ident` is not a placement argument.
+    pub fn parse_arg(&mut self, ident: &Ident, input: ParseStream) -> Result<bool> {
+        match ident.to_string().as_str() {
+            KEY_LINK_SECTION => {
+                if self.link_section.is_some() {
+                    return Err(duplicate_arg_error(ident));
+                }
+
+                input.parse::<Token![=]>()?;
+                self.link_section = Some(input.parse()?);
+            }
+            KEY_VISIBILITY => {
+                if self.visibility.is_some() {
+                    return Err(duplicate_arg_error(ident));
+                }
+
+                input.parse::<Token![=]>()?;
+                let visibility: Ident = input.parse()?;
+                if !matches!(
+                    visibility.to_string().as_str(),
+                    "hidden" | "protected" | "default"
+                ) {
+                    return Err(Error::new_spanned(
+                        &visibility,
+                        "expect `hidden`, `protected` or `default`",
+                    ));
+                }
+                self.visibility = Some(visibility);
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Returns the placement overridden by the arguments given in `other`.
+    pub fn overridden_by(&self, other: &SymbolPlacement) -> SymbolPlacement {
+        SymbolPlacement {
+            link_section: other.link_section.clone().or(self.link_section.clone()),
+            visibility: other.visibility.clone().or(self.visibility.clone()),
+        }
+    }
+
+    /// Generate the attributes placing an exported function.
+    pub fn attrs(&self) -> TokenStream {
+        let link_section = self.link_section.iter();
+        quote! { #(#[link_section = #link_section])* }
+    }
+
+    /// Generate the module-level items setting the visibility of the exported
+    /// `symbol`, with the assembler directive of the same name, configured by
+    /// `cfgs` like the function. The `default` visibility needs none, and the
+    /// others are only supported on ELF.
+    pub fn visibility_items(&self, symbol: &TokenStream, cfgs: &[Attribute]) -> TokenStream {
+        let Some(visibility) = self
+            .visibility
+            .as_ref()
+            .filter(|visibility| *visibility != "default")
+        else {
+            return quote! {};
+        };
+        let directive = format!(".{visibility} ");
+        let message = format!("`visibility = {visibility}` is only supported on ELF targets");
+        let cfgs = cfgs.iter().filter(|attr| attr.path().is_ident("cfg"));
+        let cfgs = quote! { #(#cfgs)* };
+        quote! {
+            #cfgs
+            #[cfg(not(any(
+                target_vendor = "apple",
+                target_os = "windows",
+                target_os = "uefi",
+                target_family = "wasm"
+            )))]
+            ::core::arch::global_asm!(::core::concat!(#directive, #symbol));
+            #cfgs
+            #[cfg(any(
+                target_vendor = "apple",
+                target_os = "windows",
+                target_os = "uefi",
+                target_family = "wasm"
+            ))]
+            ::core::compile_error!(#message);
+        }
+    }
+}
 
 /// Arguments for the `def_interface` attribute.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct DefInterfaceArgs {
     /// Generate caller functions for members of the interface.
     pub gen_caller: bool,
     /// Namespace for the interface. Used to avoid name collisions and must
     /// match the one in `impl_interface`.
     pub namespace: Option<String>,
+    /// Namespace the symbols with the name of the defining package, given by
+    /// `namespace = crate`. Must match the one in `impl_interface`.
+    pub crate_namespace: bool,
+    /// Call the tracing hooks on entering and exiting every interface call.
+    pub trace: bool,
+    /// Lower `impl Trait` arguments to `&dyn Trait`, and returned `impl Trait`
+    /// to `Box<dyn Trait>`. Must match the one in `impl_interface`.
+    pub lower_impl_trait: bool,
+    /// The latest version of the interface. Methods added after the first
+    /// version are tagged with `#[since(version)]`.
+    pub version: Option<u32>,
+    /// Concrete types bound to the associated types of the interface.
+    pub types: Option<Vec<(Ident, Type)>>,
+    /// Allow methods with `&self` receivers, which are called on the instance
+    /// registered by the implementation.
+    pub instance: bool,
+    /// Placement of the weak default implementations.
+    pub placement: SymbolPlacement,
+    /// What happens when the weak default implementations panic. Must match
+    /// the one in `impl_interface`.
+    pub unwind: Option<UnwindPolicy>,
+    /// Require the implementations to be checked against the definition,
+    /// which is passed to `impl_interface` through a generated macro.
+    pub checked: bool,
+    /// The build key selecting among the candidate implementations, exactly
+    /// one of which must be linked.
+    pub select: Option<LitStr>,
+    /// Generate the plugin type loading the implementation from a shared
+    /// object, which requires the `dylib` feature.
+    pub dylib: bool,
 }
 
 impl Parse for DefInterfaceArgs {
@@ -38,15 +260,91 @@ impl Parse for DefInterfaceArgs {
 
                     arg.gen_caller = true;
                 }
+                KEY_TRACE => {
+                    if arg.trace {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.trace = true;
+                }
+                KEY_LOWER_IMPL_TRAIT => {
+                    if arg.lower_impl_trait {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.lower_impl_trait = true;
+                }
                 KEY_NAMESPACE => {
-                    if arg.namespace.is_some() {
+                    if arg.namespace.is_some() || arg.crate_namespace {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    input.parse::<Token![=]>()?;
+                    if input.peek(Token![crate]) {
+                        input.parse::<Token![crate]>()?;
+                        arg.crate_namespace = true;
+                    } else {
+                        let ns_ident: Ident = input.parse()?;
+                        arg.namespace = Some(ns_ident.to_string());
+                    }
+                }
+                KEY_VERSION => {
+                    if arg.version.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.version = Some(parse_version(input)?);
+                }
+                KEY_TYPES => {
+                    if arg.types.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.types = Some(parse_types(input)?);
+                }
+                KEY_INSTANCE => {
+                    if arg.instance {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.instance = true;
+                }
+                KEY_CHECKED => {
+                    if arg.checked {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.checked = true;
+                }
+                KEY_UNWIND => {
+                    if arg.unwind.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.unwind = Some(parse_unwind(input)?);
+                }
+                KEY_DYLIB => {
+                    if arg.dylib {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+                    if cfg!(not(feature = "dylib")) {
+                        return Err(Error::new_spanned(
+                            ident,
+                            "`dylib` requires the `dylib` feature",
+                        ));
+                    }
+
+                    arg.dylib = true;
+                }
+                KEY_SELECT => {
+                    if arg.select.is_some() {
                         return Err(duplicate_arg_error(&ident));
                     }
 
                     input.parse::<Token![=]>()?;
-                    let ns_ident: Ident = input.parse()?;
-                    arg.namespace = Some(ns_ident.to_string());
+                    arg.select = Some(input.parse()?);
                 }
+                _ if arg.placement.parse_arg(&ident, input)? => {}
                 _ => {
                     return Err(unknown_arg_error(&ident));
                 }
@@ -62,30 +360,172 @@ impl Parse for DefInterfaceArgs {
 }
 
 /// Arguments for the `impl_interface` attribute.
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct ImplInterfaceArgs {
     /// Namespace for the interface. Used to avoid name collisions and must
     /// match the one in `def_interface`.
     pub namespace: Option<String>,
+    /// Namespace the symbols with the name of the package defining the
+    /// interface, given by `namespace = crate`. Must match the one in
+    /// `def_interface`.
+    pub crate_namespace: bool,
+    /// Collect call counts and accumulated durations of the implemented
+    /// methods.
+    pub stats: bool,
+    /// Lower `impl Trait` arguments to `&dyn Trait`, and returned `impl Trait`
+    /// to `Box<dyn Trait>`. Must match the one in `def_interface`.
+    pub lower_impl_trait: bool,
+    /// The version of the interface that is implemented, which must not be
+    /// newer than the one in `def_interface`.
+    pub version: Option<u32>,
+    /// The `static` of the implementing type, on which the methods with
+    /// `&self` receivers are called.
+    pub instance: Option<Path>,
+    /// The build key selecting among the candidate implementations, and the
+    /// value of it for which this implementation is exported.
+    pub select: Option<(LitStr, LitStr)>,
+    /// Export the fingerprint of the interface, so the implementation can be
+    /// loaded from a shared object.
+    pub export_dynamic: bool,
+    /// Placement of the exported functions.
+    pub placement: SymbolPlacement,
+    /// What happens when the implemented methods panic. Must match the one in
+    /// `def_interface`.
+    pub unwind: Option<UnwindPolicy>,
+    /// Check the implementation against the definition of the interface,
+    /// which must be defined with `checked` as well.
+    pub checked: bool,
+    /// The definition of the interface, passed back by the macro generated by
+    /// `def_interface` for checked implementations.
+    pub definition: Option<Box<InterfaceDefinition>>,
+}
+
+/// The definition of an interface, as passed to a checked implementation: the
+/// arguments of `def_interface` in parentheses, followed by the trait.
+pub struct InterfaceDefinition {
+    /// The arguments of `def_interface`.
+    pub args: DefInterfaceArgs,
+    /// The trait, after processing the attributes of its methods.
+    pub item: ItemTrait,
+}
+
+impl Parse for InterfaceDefinition {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let args;
+        parenthesized!(args in input);
+        Ok(InterfaceDefinition {
+            args: args.parse()?,
+            item: input.parse()?,
+        })
+    }
 }
 
 impl Parse for ImplInterfaceArgs {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut arg = ImplInterfaceArgs::default();
+        let mut select = None;
+        let mut value = None;
 
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
 
             match ident.to_string().as_str() {
                 KEY_NAMESPACE => {
-                    if arg.namespace.is_some() {
+                    if arg.namespace.is_some() || arg.crate_namespace {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    input.parse::<Token![=]>()?;
+                    if input.peek(Token![crate]) {
+                        input.parse::<Token![crate]>()?;
+                        arg.crate_namespace = true;
+                    } else {
+                        let ns_ident: Ident = input.parse()?;
+                        arg.namespace = Some(ns_ident.to_string());
+                    }
+                }
+                KEY_VERSION => {
+                    if arg.version.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.version = Some(parse_version(input)?);
+                }
+                KEY_INSTANCE => {
+                    if arg.instance.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    input.parse::<Token![=]>()?;
+                    arg.instance = Some(input.parse()?);
+                }
+                KEY_STATS => {
+                    if arg.stats {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.stats = true;
+                }
+                KEY_LOWER_IMPL_TRAIT => {
+                    if arg.lower_impl_trait {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.lower_impl_trait = true;
+                }
+                KEY_EXPORT_DYNAMIC => {
+                    if arg.export_dynamic {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+                    if cfg!(not(feature = "dylib")) {
+                        return Err(Error::new_spanned(
+                            ident,
+                            "`export_dynamic` requires the `dylib` feature",
+                        ));
+                    }
+
+                    arg.export_dynamic = true;
+                }
+                KEY_SELECT => {
+                    if select.is_some() {
                         return Err(duplicate_arg_error(&ident));
                     }
 
                     input.parse::<Token![=]>()?;
-                    let ns_ident: Ident = input.parse()?;
-                    arg.namespace = Some(ns_ident.to_string());
+                    select = Some((ident, input.parse::<LitStr>()?));
+                }
+                KEY_VALUE => {
+                    if value.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    input.parse::<Token![=]>()?;
+                    value = Some((ident, input.parse::<LitStr>()?));
+                }
+                KEY_CHECKED => {
+                    if arg.checked {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.checked = true;
+                }
+                KEY_DEFINITION => {
+                    if arg.definition.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    let definition;
+                    parenthesized!(definition in input);
+                    arg.definition = Some(Box::new(definition.parse()?));
                 }
+                KEY_UNWIND => {
+                    if arg.unwind.is_some() {
+                        return Err(duplicate_arg_error(&ident));
+                    }
+
+                    arg.unwind = Some(parse_unwind(input)?);
+                }
+                _ if arg.placement.parse_arg(&ident, input)? => {}
                 _ => {
                     return Err(unknown_arg_error(&ident));
                 }
@@ -96,6 +536,17 @@ impl Parse for ImplInterfaceArgs {
             }
         }
 
+        arg.select = match (select, value) {
+            (Some((_, key)), Some((_, value))) => Some((key, value)),
+            (None, None) => None,
+            (Some((ident, _)), None) | (None, Some((ident, _))) => {
+                return Err(Error::new_spanned(
+                    ident,
+                    "`select` and `value` must be specified together",
+                ));
+            }
+        };
+
         Ok(arg)
     }
 }
@@ -104,6 +555,9 @@ impl Parse for ImplInterfaceArgs {
 pub struct CallInterface {
     /// Optional namespace for the interface.
     pub namespace: Option<String>,
+    /// Dispatch to the implementation bound with `bind!` instead of through
+    /// the exported symbol.
+    pub bound: bool,
     /// Path to the interface method to call.
     pub path: Path,
     /// Arguments to pass to the interface method.
@@ -113,21 +567,26 @@ pub struct CallInterface {
 impl Parse for CallInterface {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut namespace = None;
+        let mut bound = false;
         let content;
 
         let mut path: Path = input.parse()?;
-        // try to parse namespace if any, we just assume that no programmer with
-        // basic sanity would name a trait "namespace", and, anyway, a valid
-        // path here requires at least 2 segments (Trait::func).
-        if let Some(ident) = path.get_ident() {
-            if ident == KEY_NAMESPACE {
+        // try to parse namespace and `bound` if any, we just assume that no
+        // programmer with basic sanity would name a trait "namespace" or
+        // "bound", and, anyway, a valid path here requires at least 2 segments
+        // (Trait::func).
+        while let Some(ident) = path.get_ident() {
+            if ident == KEY_NAMESPACE && namespace.is_none() {
                 input.parse::<Token![=]>()?;
                 let ns_ident: Ident = input.parse()?;
                 namespace = Some(ns_ident.to_string());
-
-                input.parse::<Token![,]>()?;
-                path = input.parse()?;
+            } else if ident == KEY_BOUND && !bound {
+                bound = true;
+            } else {
+                break;
             }
+            input.parse::<Token![,]>()?;
+            path = input.parse()?;
         }
 
         let args = if input.peek(Token![,]) {
@@ -141,8 +600,36 @@ impl Parse for CallInterface {
         };
         Ok(CallInterface {
             namespace,
+            bound,
             path,
             args,
         })
     }
 }
+
+/// Arguments for the `bind!` macro.
+pub struct BindInterface {
+    /// Visibility of the generated type alias.
+    pub vis: Visibility,
+    /// Path to the interface trait.
+    pub trait_path: Path,
+    /// The implementing type bound to the interface.
+    pub ty: Type,
+}
+
+impl Parse for BindInterface {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let vis = input.parse()?;
+        let trait_path = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let ty = input.parse()?;
+        if !input.is_empty() {
+            input.parse::<Token![;]>()?;
+        }
+        Ok(BindInterface {
+            vis,
+            trait_path,
+            ty,
+        })
+    }
+}
@@ -3,24 +3,46 @@
 use proc_macro2::TokenStream;
 #[cfg(feature = "weak_default")]
 use quote::format_ident;
-use quote::quote;
-use syn::{parse_quote, Error, ItemTrait, TraitItem};
-#[cfg(feature = "weak_default")]
+use quote::{quote, ToTokens};
 use syn::{
-    punctuated::Punctuated, visit_mut::VisitMut, Block, Expr, ExprPath, Ident, Path, PathSegment,
-    Signature,
+    parse::ParseStream, parse_quote, Attribute, Error, FnArg, Ident, ItemTrait, LitInt, Macro,
+    Path, PathArguments, ReturnType, Signature, Token, TraitBoundModifier, TraitItem, TraitItemFn,
+    Type, TypeParamBound,
 };
-
 #[cfg(feature = "weak_default")]
+use syn::{punctuated::Punctuated, visit_mut::VisitMut, Block, Expr, ExprPath, PathSegment};
+
 use std::collections::HashMap;
 
-use crate::args::DefInterfaceArgs;
-use crate::errors::generic_not_allowed_error;
+use crate::args::{DefInterfaceArgs, SymbolPlacement, UnwindPolicy};
+use crate::dylib::{dylib_support_items, fingerprint, plugin_method_items};
 #[cfg(not(feature = "weak_default"))]
 use crate::errors::weak_default_required_error;
+use crate::errors::{
+    duplicate_arg_error, fallback_requires_weak_default_error, generic_not_allowed_error,
+    unknown_arg_error,
+};
+#[cfg(feature = "weak_default")]
+use crate::lowering::extern_def_sig;
+use crate::lowering::{
+    alloc_support_items, extern_decl_sig, find_self_type, forwarding_sig, lower_async_body,
+    lower_async_sig, lower_boxed_return_body, lower_impl_trait_sig, lowered_caller_args,
+    self_assoc_type, strip_receiver, substitute_self_types,
+};
 use crate::naming::{
-    alias_guard_name, extern_fn_mod_name, extern_fn_name, extract_caller_args, namespace_guard_name,
+    alias_guard_name, checked_guard_name, crate_namespace, crate_namespace_guard_name,
+    crate_namespace_macro_name, default_fn_name, defaults_trait_name, definition_macro_alias,
+    definition_macro_name, dylib_mod_name, extern_fn_mod_name, extern_fn_name, extract_caller_args,
+    fingerprint_const_name, fingerprint_fn_name, implemented_guard_name, latest_version_name,
+    methods_mod_name, namespace_check_fn_name, namespace_guard_name, namespace_macro_name,
+    plugin_type_name, proxy_type_name, required_methods_guard_name, resolver_new_fn_name,
+    resolver_type_name, select_guard_name, select_marker_name, select_marker_ref_name,
+    trace_hook_fn_name, trace_wrapper_name, type_binding_trait_name, types_mod_name,
+    unwind_guard_name, version_fn_name, version_guard_name,
 };
+use crate::unwind::unwind_support_items;
+#[cfg(feature = "weak_default")]
+use crate::unwind::wrap_call;
 use crate::validator::validate_fn_signature;
 
 /// Rewrite all references to `Self::some_method` in the default body.
@@ -37,14 +59,12 @@ use crate::validator::validate_fn_signature;
 fn rewrite_self_in_default_body(
     default_body: &Block,
     trait_name: &Ident,
-    namespace: Option<&str>,
-    method_signatures: &HashMap<String, Signature>,
+    method_signatures: &HashMap<String, Vec<(Vec<Attribute>, Signature)>>,
 ) -> TokenStream {
     /// Visitor that rewrites `Self::method_name` references using proxy functions.
     struct SelfRefRewriter<'a> {
         trait_name: &'a Ident,
-        namespace: Option<&'a str>,
-        method_signatures: &'a HashMap<String, Signature>,
+        method_signatures: &'a HashMap<String, Vec<(Vec<Attribute>, Signature)>>,
         /// Generated proxy functions (method_name -> proxy_fn_code)
         /// Each method only generates one proxy function
         generated_proxies: HashMap<String, TokenStream>,
@@ -80,26 +100,31 @@ fn rewrite_self_in_default_body(
                 return Some(Self::proxy_name(&method_name));
             }
 
-            // Generate new proxy function
-            let sig = self.method_signatures.get(&method_key)?;
+            // Generate new proxy function, one for each configuration of the
+            // method if it is declared multiple times under different `#[cfg]`s
+            let variants = self.method_signatures.get(&method_key)?;
             let mod_name = extern_fn_mod_name(self.trait_name);
-            let extern_fn = extern_fn_name(self.namespace, &self.trait_name, &method_name);
+            let methods_mod = methods_mod_name();
             let proxy_name = Self::proxy_name(&method_name);
 
-            // Extract arguments for the call
-            let caller_args = extract_caller_args(sig).ok()?;
+            let mut proxy_fn = TokenStream::new();
+            for (cfgs, sig) in variants {
+                // Extract arguments for the call
+                let caller_args = extract_caller_args(sig).ok()?;
 
-            // Clone signature and rename
-            let mut proxy_sig = sig.clone();
-            proxy_sig.ident = proxy_name.clone();
+                // Clone signature and rename
+                let mut proxy_sig = forwarding_sig(sig);
+                proxy_sig.ident = proxy_name.clone();
 
-            // Generate the proxy function
-            let proxy_fn = quote! {
-                #[allow(non_snake_case)]
-                #proxy_sig {
-                    unsafe { #mod_name :: #extern_fn ( #caller_args ) }
-                }
-            };
+                // Generate the proxy function
+                proxy_fn.extend(quote! {
+                    #(#cfgs)*
+                    #[allow(non_snake_case)]
+                    #proxy_sig {
+                        unsafe { #mod_name :: #methods_mod :: #method_name ( #caller_args ) }
+                    }
+                });
+            }
 
             self.generated_proxies.insert(method_key, proxy_fn);
             Some(proxy_name)
@@ -139,7 +164,6 @@ fn rewrite_self_in_default_body(
     let mut body = default_body.clone();
     let mut rewriter = SelfRefRewriter {
         trait_name,
-        namespace,
         method_signatures,
         generated_proxies: HashMap::new(),
     };
@@ -160,11 +184,514 @@ fn rewrite_self_in_default_body(
     }
 }
 
+/// Resolve the path to the type named by [`resolver_type_name`] of a supertrait
+/// interface, as seen from inside the generated extern function module.
+///
+/// Paths starting with `self` or `super` are relative to the module where the
+/// trait is defined, so they are adjusted by one level. Other paths resolve
+/// through the `use super::*` glob import of the generated module.
+fn supertrait_resolver_path(bound: &TypeParamBound) -> Result<Option<Path>, Error> {
+    let bound = match bound {
+        TypeParamBound::Trait(bound) => bound,
+        // Lifetime bounds do not affect the generated symbols.
+        TypeParamBound::Lifetime(_) => return Ok(None),
+        _ => return Err(Error::new_spanned(bound, "unsupported supertrait bound")),
+    };
+    if !matches!(bound.modifier, TraitBoundModifier::None) || bound.lifetimes.is_some() {
+        return Err(Error::new_spanned(
+            bound,
+            "supertraits of an interface must be plain interface traits",
+        ));
+    }
+
+    let mut path = bound.path.clone();
+    if path
+        .segments
+        .iter()
+        .any(|seg| !matches!(seg.arguments, PathArguments::None))
+    {
+        return Err(generic_not_allowed_error(&bound.path));
+    }
+
+    let trait_name = path.segments.pop().unwrap().into_value().ident;
+    path.segments.push(extern_fn_mod_name(&trait_name).into());
+    path.segments.push(resolver_type_name().into());
+
+    // The path is resolved in the module generated next to the trait, where
+    // `self` of the trait's module is `super`.
+    if path.leading_colon.is_none() {
+        let first = &mut path.segments[0].ident;
+        if first == "self" {
+            *first = Ident::new("super", first.span());
+        } else if first == "super" {
+            path = parse_quote!(super::#path);
+        }
+    }
+    Ok(Some(path))
+}
+
+/// Parse the arguments of an `opaque!(size = N, align = N)` associated type.
+fn parse_opaque_layout(mac: &Macro) -> Result<(LitInt, LitInt), Error> {
+    let mut size = None;
+    let mut align = None;
+    mac.parse_body_with(|input: ParseStream| {
+        while !input.is_empty() {
+            let ident: Ident = input.parse()?;
+            let slot = match ident.to_string().as_str() {
+                "size" => &mut size,
+                "align" => &mut align,
+                _ => return Err(unknown_arg_error(&ident)),
+            };
+            if slot.is_some() {
+                return Err(duplicate_arg_error(&ident));
+            }
+            input.parse::<Token![=]>()?;
+            *slot = Some(input.parse::<LitInt>()?);
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(())
+    })?;
+
+    let (Some(size), Some(align)) = (size, align) else {
+        return Err(Error::new_spanned(
+            mac,
+            "opaque types must be declared with `opaque!(size = N, align = N)`",
+        ));
+    };
+    size.base10_parse::<usize>()?;
+    if !align.base10_parse::<usize>()?.is_power_of_two() {
+        return Err(Error::new_spanned(
+            align,
+            "alignment must be a power of two",
+        ));
+    }
+    Ok((size, align))
+}
+
+/// Bind the associated types of the interface to the concrete types given by
+/// the `types` option, and substitute them in the signatures and the default
+/// implementations of the methods. Associated types declared as
+/// `opaque!(size = N, align = N)` are bound by the implementation instead, and
+/// passed across the interface in opaque storage of the given layout.
+///
+/// Returns the module exporting the concrete types (or the opaque storage)
+/// under the names of the associated types, along with the conversions from
+/// the types of the implementation, against which `impl_interface` checks
+/// them. The names of the opaque types are returned as well.
+fn bind_assoc_types(
+    ast: &mut ItemTrait,
+    types: &[(Ident, Type)],
+) -> Result<(TokenStream, Vec<Ident>), Error> {
+    let mut names = vec![];
+    let mut bindings = vec![];
+    let mut opaque_types = vec![];
+    for item in &mut ast.items {
+        let TraitItem::Type(assoc) = item else {
+            continue;
+        };
+        let name = &assoc.ident;
+        if !assoc.generics.params.is_empty() {
+            return Err(generic_not_allowed_error(&assoc.generics));
+        }
+        names.push(name.clone());
+        let binding = type_binding_trait_name(name);
+        let bound = types.iter().find(|(bound, _)| bound == name);
+
+        let Some((_, Type::Macro(opaque))) = &assoc.default else {
+            let Some((_, ty)) = bound else {
+                return Err(Error::new_spanned(
+                    &*assoc,
+                    format!(
+                        "associated type `{name}` must be bound with `types({name} = ...)`, or declared as `opaque!(size = N, align = N)`"
+                    ),
+                ));
+            };
+            bindings.push(quote! {
+                pub type #name = #ty;
+
+                impl #binding<#name> for #name {
+                    const FITS: () = ();
+                    #[inline]
+                    unsafe fn wrap(value: #name) -> Self {
+                        value
+                    }
+                    #[inline]
+                    unsafe fn unwrap(self) -> #name {
+                        self
+                    }
+                    #[inline]
+                    unsafe fn unwrap_ref(&self) -> &#name {
+                        self
+                    }
+                    #[inline]
+                    unsafe fn unwrap_mut(&mut self) -> &mut #name {
+                        self
+                    }
+                }
+            });
+            continue;
+        };
+        if !opaque.mac.path.is_ident("opaque") {
+            return Err(Error::new_spanned(
+                opaque,
+                "expect `opaque!(size = N, align = N)`",
+            ));
+        }
+        if bound.is_some() {
+            return Err(Error::new_spanned(
+                name,
+                format!("opaque type `{name}` can not be bound with `types`"),
+            ));
+        }
+        let (size, align) = parse_opaque_layout(&opaque.mac)?;
+        let message =
+            format!("the type of the implementation does not fit in opaque type `{name}`");
+        bindings.push(quote! {
+            /// Opaque storage of the associated type, whose type is only known
+            /// to the implementation.
+            #[repr(C, align(#align))]
+            pub struct #name {
+                _storage: [::core::mem::MaybeUninit<u8>; #size],
+                _marker: ::core::marker::PhantomData<*mut ()>,
+            }
+
+            impl<T> #binding<T> for #name {
+                const FITS: () = ::core::assert!(
+                    ::core::mem::size_of::<T>() <= #size && ::core::mem::align_of::<T>() <= #align,
+                    #message
+                );
+                #[inline]
+                unsafe fn wrap(value: T) -> Self {
+                    let () = <Self as #binding<T>>::FITS;
+                    let mut storage = ::core::mem::MaybeUninit::<Self>::uninit();
+                    storage.as_mut_ptr().cast::<T>().write(value);
+                    storage.assume_init()
+                }
+                #[inline]
+                unsafe fn unwrap(self) -> T {
+                    let () = <Self as #binding<T>>::FITS;
+                    (&self as *const Self).cast::<T>().read()
+                }
+                #[inline]
+                unsafe fn unwrap_ref(&self) -> &T {
+                    &*(self as *const Self).cast::<T>()
+                }
+                #[inline]
+                unsafe fn unwrap_mut(&mut self) -> &mut T {
+                    &mut *(self as *mut Self).cast::<T>()
+                }
+            }
+        });
+        opaque_types.push(name.clone());
+        assoc.default = None;
+    }
+    if let Some((name, _)) = types.iter().find(|(name, _)| !names.contains(name)) {
+        return Err(Error::new_spanned(
+            name,
+            format!("`{name}` is not an associated type of the interface"),
+        ));
+    }
+    if names.is_empty() {
+        return Ok((quote! {}, opaque_types));
+    }
+
+    let resolve = |name: &Ident| {
+        types
+            .iter()
+            .find(|(bound, _)| bound == name)
+            .map(|(_, ty)| ty.clone())
+    };
+    for item in &mut ast.items {
+        if let TraitItem::Fn(method) = item {
+            substitute_self_types(Some(&mut method.sig), method.default.as_mut(), &resolve);
+        }
+    }
+
+    let types_mod = types_mod_name();
+    let binding_traits = names.iter().map(|name| {
+        let binding = type_binding_trait_name(name);
+        quote! {
+            pub trait #binding<T>: Sized {
+                const FITS: ();
+                unsafe fn wrap(value: T) -> Self;
+                unsafe fn unwrap(self) -> T;
+                unsafe fn unwrap_ref(&self) -> &T;
+                unsafe fn unwrap_mut(&mut self) -> &mut T;
+            }
+        }
+    });
+    let types_mod = quote! {
+        #[allow(non_camel_case_types)]
+        pub mod #types_mod {
+            use super::*;
+            #(#binding_traits)*
+            #(#bindings)*
+        }
+    };
+    Ok((types_mod, opaque_types))
+}
+
+/// Check that the opaque associated types only appear in the signature where
+/// they can be converted to and from the types of the implementation, that is,
+/// passed by value or by reference, or returned by value.
+fn check_opaque_positions(method: &TraitItemFn, opaque_types: &[Ident]) -> Result<(), Error> {
+    let is_opaque =
+        |ty: &Type| self_assoc_type(ty).map_or(false, |name| opaque_types.contains(name));
+    let position_error = |ty: &Type, message: &str| Err(Error::new_spanned(ty, message));
+
+    for arg in &method.sig.inputs {
+        let FnArg::Typed(arg) = arg else {
+            continue;
+        };
+        match &*arg.ty {
+            Type::Reference(reference) if is_opaque(&reference.elem) => {
+                if method.default.is_some() {
+                    return position_error(
+                        &arg.ty,
+                        "opaque types can not be passed by reference to methods with default implementations",
+                    );
+                }
+            }
+            ty if is_opaque(ty) => {}
+            ty => {
+                if let Some(ty) = find_self_type(ty, opaque_types) {
+                    return position_error(
+                        ty,
+                        "opaque types can only be passed by value or by reference",
+                    );
+                }
+            }
+        }
+    }
+    if let ReturnType::Type(_, ty) = &method.sig.output {
+        if !is_opaque(ty) {
+            if let Some(ty) = find_self_type(ty, opaque_types) {
+                return position_error(ty, "opaque types can only be returned by value");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Collect the `#[cfg]` and `#[cfg_attr]` attributes of a method, which are
+/// copied to the items generated for it.
+fn cfg_attrs(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg") || attr.path().is_ident("cfg_attr"))
+        .cloned()
+        .collect()
+}
+
+/// Parse the `#[interface(...)]` attribute of a method, returning the method
+/// given by `replaced_by`, and adding the placement arguments to `placement`.
+fn parse_interface_attr(
+    attr: &Attribute,
+    placement: &mut SymbolPlacement,
+) -> Result<Option<Ident>, Error> {
+    let mut replaced_by = None;
+    attr.parse_nested_meta(|meta| {
+        let ident = meta.path.require_ident()?;
+        if placement.parse_arg(ident, meta.input)? {
+            return Ok(());
+        }
+        if ident != "replaced_by" {
+            return Err(unknown_arg_error(ident));
+        }
+        if replaced_by.is_some() {
+            return Err(duplicate_arg_error(ident));
+        }
+        replaced_by = Some(meta.value()?.parse::<Ident>()?);
+        Ok(())
+    })?;
+    Ok(replaced_by)
+}
+
+/// Process the `#[since(version)]` and `#[interface(...)]` attributes of the
+/// methods, and strip them. Returns the placements of the weak default
+/// implementations given in the `#[interface(...)]` attributes, by the index
+/// of the methods.
+///
+/// Methods replaced by others are given a default implementation forwarding to
+/// the replacement, so old implementations keep working. Methods added after
+/// the first version are given an "unsupported" default implementation if they
+/// have none, which is used by the implementations of older versions. These
+/// require the `weak_default` feature, unless the interface is `checked`. The
+/// implementations of newer versions are checked to implement those methods.
+fn process_method_attrs(
+    ast: &mut ItemTrait,
+    version: Option<u32>,
+    checked: bool,
+) -> Result<HashMap<usize, SymbolPlacement>, Error> {
+    let trait_name = &ast.ident;
+    let method_names: Vec<Ident> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            TraitItem::Fn(method) => Some(method.sig.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let mut placements = HashMap::new();
+    let mut implemented_guards: Vec<TraitItem> = vec![];
+    let mut required_checks = vec![];
+    for (index, item) in ast.items.iter_mut().enumerate() {
+        let TraitItem::Fn(method) = item else {
+            continue;
+        };
+        let mut since = None;
+        let mut replaced_by = None;
+        let mut placement = SymbolPlacement::default();
+        for attr in &method.attrs {
+            if attr.path().is_ident("interface") {
+                if let Some(new_fn) = parse_interface_attr(attr, &mut placement)? {
+                    if replaced_by.is_some() {
+                        return Err(Error::new_spanned(attr, "duplicate `replaced_by` argument"));
+                    }
+                    if new_fn == method.sig.ident || !method_names.contains(&new_fn) {
+                        return Err(Error::new_spanned(
+                            new_fn,
+                            "`replaced_by` must name another method of the interface",
+                        ));
+                    }
+                    replaced_by = Some(new_fn);
+                }
+                continue;
+            }
+            if !attr.path().is_ident("since") {
+                continue;
+            }
+            let Some(latest) = version else {
+                return Err(Error::new_spanned(
+                    attr,
+                    "`#[since]` requires the `version` option of `def_interface`",
+                ));
+            };
+            let lit: LitInt = attr.parse_args()?;
+            let value = lit.base10_parse::<u32>()?;
+            if since.is_some() {
+                return Err(Error::new_spanned(attr, "duplicate `#[since]` attribute"));
+            }
+            if value == 0 || value > latest {
+                return Err(Error::new_spanned(
+                    lit,
+                    format!("`#[since]` must be between 1 and the interface version {latest}"),
+                ));
+            }
+            since = Some(value);
+        }
+        method
+            .attrs
+            .retain(|attr| !attr.path().is_ident("since") && !attr.path().is_ident("interface"));
+        placements.insert(index, placement);
+
+        let cfgs = cfg_attrs(&method.attrs);
+        let implemented_guard = implemented_guard_name(&method.sig.ident);
+        if version.is_some() {
+            implemented_guards.push(parse_quote!(
+                #(#cfgs)*
+                #[allow(non_upper_case_globals)]
+                #[doc(hidden)]
+                const #implemented_guard: bool = false;
+            ));
+        }
+
+        if let Some(new_fn) = replaced_by {
+            if method.default.is_some() {
+                return Err(Error::new_spanned(
+                    &method.sig.ident,
+                    "methods replaced by others can not have a default implementation",
+                ));
+            }
+            if cfg!(not(feature = "weak_default")) && !checked {
+                let reason = format!("is replaced by `{new_fn}`");
+                return Err(fallback_requires_weak_default_error(method, &reason));
+            }
+            let args = extract_caller_args(&method.sig)?;
+            let mut call = quote! { Self::#new_fn(#args) };
+            if method.sig.unsafety.is_some() {
+                call = quote! { unsafe { #call } };
+            }
+            method.default = Some(parse_quote! {{ #call }});
+            method.sig = forwarding_sig(&method.sig);
+        }
+
+        let Some(since) = since.filter(|since| *since > 1) else {
+            continue;
+        };
+        if method.default.is_none() {
+            if cfg!(not(feature = "weak_default")) && !checked {
+                let reason = "is added after the first version of the interface";
+                return Err(fallback_requires_weak_default_error(method, reason));
+            }
+            let fn_name = method.sig.ident.clone();
+            let message = format!(
+                "`{trait_name}::{fn_name}` (since version {since}) is not supported by the implementation"
+            );
+            let args = extract_caller_args(&method.sig)?;
+            let args = args.iter();
+            method.default = Some(parse_quote! {{
+                #(let _ = #args;)*
+                ::core::unimplemented!(#message)
+            }});
+            method.sig = forwarding_sig(&method.sig);
+
+            let version_guard = version_guard_name();
+            let message = format!(
+                "`{trait_name}::{fn_name}` (since version {since}) must be implemented by the implementations of version {since} or newer"
+            );
+            required_checks.push(quote! {
+                #(#cfgs)*
+                let () = ::core::assert!(
+                    Self::#version_guard < #since || Self::#implemented_guard,
+                    #message
+                );
+            });
+        }
+    }
+
+    // The implementations set the flags of the methods they implement, and
+    // evaluate the check of the methods required by their version.
+    if version.is_some() {
+        let required_guard = required_methods_guard_name();
+        ast.items.extend(implemented_guards);
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #required_guard: () = {
+                #(#required_checks)*
+            };
+        ));
+    }
+    Ok(placements)
+}
+
 /// The implementation of the [`crate::def_interface`] attribute macro.
 pub fn def_interface(
     mut ast: ItemTrait,
     macro_arg: DefInterfaceArgs,
+    attr: TokenStream,
 ) -> Result<TokenStream, Error> {
+    #[cfg_attr(not(feature = "weak_default"), allow(unused_variables))]
+    let placements = process_method_attrs(&mut ast, macro_arg.version, macro_arg.checked)?;
+    // Checked implementations are given the trait as it is here. They only
+    // need to know which methods have default implementations.
+    let definition = macro_arg.checked.then(|| {
+        let mut item = ast.clone();
+        for item in &mut item.items {
+            if let TraitItem::Fn(method) = item {
+                if let Some(default_body) = &mut method.default {
+                    *default_body = parse_quote!({});
+                }
+            }
+        }
+        item.to_token_stream()
+    });
+    let (types_mod, opaque_types) =
+        bind_assoc_types(&mut ast, macro_arg.types.as_deref().unwrap_or_default())?;
+
     let trait_name = &ast.ident;
     let vis = &ast.vis;
 
@@ -173,111 +700,682 @@ pub fn def_interface(
     }
 
     let mod_name = extern_fn_mod_name(trait_name);
+    let methods_mod = methods_mod_name();
+    let resolver_name = resolver_type_name();
+    let resolver_new = resolver_new_fn_name();
+    let namespace_check = namespace_check_fn_name(macro_arg.namespace.as_deref(), trait_name);
+    let types_mod_name = types_mod_name();
+
+    // With `namespace = crate`, the symbols are namespaced with the name of
+    // the package being built, which is the one defining the interface.
+    let symbol_ns = if macro_arg.crate_namespace {
+        let package_name = std::env::var("CARGO_PKG_NAME").map_err(|_| {
+            Error::new_spanned(
+                trait_name,
+                "`namespace = crate` requires `CARGO_PKG_NAME` to be set, as Cargo does",
+            )
+        })?;
+        Some(crate_namespace(&package_name))
+    } else {
+        macro_arg.namespace.clone()
+    };
+    let types_path = quote! { #mod_name::#types_mod_name };
+
+    // Opaque types are passed in their opaque storage outside of the trait. The
+    // storage is referred to relative to the generated module from inside it.
+    // Receivers are dropped as well, as the instance is only known to the
+    // implementation.
+    let resolve_opaque = |name: &Ident| -> Option<Type> {
+        opaque_types
+            .contains(name)
+            .then(|| parse_quote!(#types_path::#name))
+    };
+    let resolve_opaque_in_mod = |name: &Ident| -> Option<Type> {
+        opaque_types
+            .contains(name)
+            .then(|| parse_quote!(#types_mod_name::#name))
+    };
+    let opaque_sig = |sig: &Signature, in_mod: bool| {
+        let mut sig = strip_receiver(sig);
+        if in_mod {
+            substitute_self_types(Some(&mut sig), None, &resolve_opaque_in_mod);
+        } else {
+            substitute_self_types(Some(&mut sig), None, &resolve_opaque);
+        }
+        sig
+    };
+
+    // The resolver dereferences to the ones of supertrait interfaces, so their
+    // methods are callable through this interface as well. The resolvers of
+    // all the supertraits are chained with their type parameters.
+    let mut supertrait_resolvers = vec![];
+    for bound in &ast.supertraits {
+        if let Some(path) = supertrait_resolver_path(bound)? {
+            supertrait_resolvers.push(path);
+        }
+    }
+    let (resolver_chain, resolver_chain_new) = supertrait_resolvers
+        .iter()
+        .rev()
+        .fold((quote! { __Next }, quote! { next }), |(ty, new), path| {
+            (quote! { #path<#ty> }, quote! { #path::#resolver_new(#new) })
+        });
+
+    // Lower `async` methods to ones returning boxed futures, and `impl Trait`
+    // types to trait objects if enabled. The original signatures are kept for
+    // the callers to accept `impl Trait` arguments.
+    let mut needs_alloc = false;
+    let mut original_signatures: HashMap<usize, Signature> = HashMap::new();
+    for (index, item) in ast.items.iter_mut().enumerate() {
+        if let TraitItem::Fn(method) = item {
+            if lower_async_sig(&mut method.sig, &quote! { #mod_name })? {
+                needs_alloc = true;
+                if let Some(default_body) = &mut method.default {
+                    *default_body = lower_async_body(default_body, &quote! { #mod_name });
+                }
+            }
+            if macro_arg.lower_impl_trait {
+                let original_sig = method.sig.clone();
+                if lower_impl_trait_sig(&mut method.sig, &quote! { #mod_name })? {
+                    needs_alloc = true;
+                    if let Some(default_body) = &mut method.default {
+                        *default_body =
+                            lower_boxed_return_body(default_body, &quote! { #mod_name });
+                    }
+                }
+                original_signatures.insert(index, original_sig);
+            }
+        }
+    }
+    let alloc_support = needs_alloc.then(alloc_support_items);
 
     // Collect all method signatures for use in rewriting Self::method references
     #[cfg(feature = "weak_default")]
-    let mut method_signatures: HashMap<String, Signature> = HashMap::new();
+    let mut method_signatures: HashMap<String, Vec<(Vec<Attribute>, Signature)>> = HashMap::new();
     #[cfg(feature = "weak_default")]
     for item in &ast.items {
         if let TraitItem::Fn(method) = item {
             let sig = &method.sig;
-            method_signatures.insert(sig.ident.to_string(), sig.clone());
+            method_signatures
+                .entry(sig.ident.to_string())
+                .or_default()
+                .push((cfg_attrs(&method.attrs), opaque_sig(sig, false)));
         }
     }
 
+    // Declare the tracing hooks if enabled.
+    let trace_enter = trace_hook_fn_name("on_enter");
+    let trace_exit = trace_hook_fn_name("on_exit");
     let mut extern_fn_list = vec![];
+    let mut extern_blocks = vec![];
+    if macro_arg.trace {
+        extern_fn_list.push(quote! {
+            fn #trace_enter(trait_name: &'static str, method: &'static str, namespace: &'static str);
+            fn #trace_exit(trait_name: &'static str, method: &'static str, namespace: &'static str);
+        });
+    }
+    let mut method_reexports = vec![];
+    let mut trace_wrappers = vec![];
     let mut callers: Vec<TokenStream> = vec![];
+    let mut proxy_items: Vec<TokenStream> = vec![];
+    let mut resolver_methods: Vec<TokenStream> = vec![];
+    let mut plugin_fields: Vec<TokenStream> = vec![];
+    let mut plugin_loads: Vec<TokenStream> = vec![];
+    let mut plugin_methods: Vec<TokenStream> = vec![];
+    let mut default_fns: Vec<TokenStream> = vec![];
+    #[cfg_attr(not(feature = "weak_default"), allow(unused_mut))]
+    let mut visibility_items: Vec<TokenStream> = vec![];
+    let mut fingerprint_parts = vec![
+        trait_name.to_string(),
+        symbol_ns.clone().unwrap_or_default(),
+        macro_arg.version.unwrap_or_default().to_string(),
+    ];
 
-    for item in &mut ast.items {
+    for (index, item) in ast.items.iter_mut().enumerate() {
         if let TraitItem::Fn(method) = item {
+            // Every generated item of the method is configured like the method.
+            let cfgs = cfg_attrs(&method.attrs);
+
+            // Deprecate the generated items of deprecated methods, which use
+            // one another and the deprecated method itself. Deprecations may
+            // also be hidden in the `#[cfg_attr]`s.
+            let deprecated: Vec<_> = method
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("deprecated"))
+                .cloned()
+                .collect();
+            let allow_deprecated = (!deprecated.is_empty()
+                || cfgs.iter().any(|attr| attr.path().is_ident("cfg_attr")))
+            .then(|| quote! { #[allow(deprecated)] });
+            if allow_deprecated.is_some() {
+                method.attrs.push(parse_quote!(#[allow(deprecated)]));
+            }
+
             let sig = &method.sig;
             let fn_name = &sig.ident;
 
             // Validate signature: reject generic parameters and receivers
-            validate_fn_signature(sig)?;
+            validate_fn_signature(sig, macro_arg.instance)?;
+            check_opaque_positions(method, &opaque_types)?;
+            // The resolver dereferences to the ones of the supertraits with
+            // `Deref`, whose method would shadow the ones of the supertraits.
+            if fn_name == "deref" {
+                return Err(Error::new_spanned(
+                    fn_name,
+                    "`deref` is reserved, since `call_interface!` dereferences to the supertrait interfaces",
+                ));
+            }
+            if sig.receiver().is_some() && method.default.is_some() {
+                return Err(Error::new_spanned(
+                    method,
+                    "methods with receivers can not have default implementations, since the instance is only known to the implementation",
+                ));
+            }
+            let ext_sig = &opaque_sig(sig, false);
+            let mod_sig = &opaque_sig(sig, true);
 
-            let extern_fn_name =
-                extern_fn_name(macro_arg.namespace.as_deref(), trait_name, fn_name);
+            let extern_fn_name = extern_fn_name(symbol_ns.as_deref(), trait_name, fn_name);
 
-            let mut extern_fn_sig = sig.clone();
+            let (abi, mut extern_fn_sig) = extern_decl_sig(mod_sig);
             extern_fn_sig.ident = extern_fn_name.clone();
 
-            extern_fn_list.push(quote! {
-                pub #extern_fn_sig;
+            // Functions with a custom ABI are declared in their own blocks.
+            if sig.abi.is_some() {
+                extern_blocks.push(quote! {
+                    #abi {
+                        #(#cfgs)*
+                        #(#deprecated)*
+                        pub #extern_fn_sig;
+                    }
+                });
+            } else {
+                extern_fn_list.push(quote! {
+                    #(#cfgs)*
+                    #(#deprecated)*
+                    pub #extern_fn_sig;
+                });
+            }
+
+            // Wrap the extern function with the tracing hooks if enabled, and
+            // export the one to be called under the method name.
+            if macro_arg.trace {
+                let wrapper_name = trace_wrapper_name(fn_name);
+                let mut wrapper_sig = forwarding_sig(mod_sig);
+                wrapper_sig.ident = wrapper_name.clone();
+                wrapper_sig.abi = None;
+                wrapper_sig.unsafety = Some(Default::default());
+                let caller_args = extract_caller_args(sig)?;
+                let trait_str = trait_name.to_string();
+                let fn_str = fn_name.to_string();
+                let ns_str = symbol_ns.as_deref().unwrap_or_default();
+                trace_wrappers.push(quote! {
+                    #(#cfgs)*
+                    #(#deprecated)*
+                    #allow_deprecated
+                    #[inline]
+                    pub #wrapper_sig {
+                        #trace_enter(#trait_str, #fn_str, #ns_str);
+                        let ret = #extern_fn_name( #caller_args );
+                        #trace_exit(#trait_str, #fn_str, #ns_str);
+                        ret
+                    }
+                });
+                method_reexports.push(quote! {
+                    #(#cfgs)*
+                    #allow_deprecated
+                    pub use super::#wrapper_name as #fn_name;
+                });
+            } else {
+                method_reexports.push(quote! {
+                    #(#cfgs)*
+                    #allow_deprecated
+                    pub use super::#extern_fn_name as #fn_name;
+                });
+            }
+
+            // Forward the method of the resolver to the exported function. It
+            // is only unsafe if the method is, so the callers keep its safety
+            // contract.
+            let mut resolver_sig = forwarding_sig(mod_sig);
+            resolver_sig.abi = None;
+            resolver_sig.unsafety = sig.unsafety;
+            resolver_sig.inputs.insert(0, parse_quote!(&self));
+            let resolver_args = extract_caller_args(sig)?;
+            resolver_methods.push(quote! {
+                #(#cfgs)*
+                #(#deprecated)*
+                #allow_deprecated
+                #[inline]
+                pub #resolver_sig {
+                    unsafe { #methods_mod::#fn_name( #resolver_args ) }
+                }
             });
 
-            // Reject default implementations when weak_default feature is not enabled
+            // Load the function from shared objects if enabled.
+            if macro_arg.dylib {
+                let (field, load, method) = plugin_method_items(
+                    ext_sig,
+                    &mod_name,
+                    &extern_fn_name,
+                    &cfgs,
+                    &quote! { #(#deprecated)* #allow_deprecated },
+                )?;
+                plugin_fields.push(field);
+                plugin_loads.push(load);
+                plugin_methods.push(method);
+                fingerprint_parts.push(quote!(#ext_sig).to_string());
+            }
+
+            // Reject default implementations when weak_default feature is not
+            // enabled, unless the checked implementations copy them.
             #[cfg(not(feature = "weak_default"))]
-            if method.default.is_some() {
+            if method.default.is_some() && !macro_arg.checked {
                 return Err(weak_default_required_error(method));
             }
 
             // Generate weak symbol function for methods with default implementations
             #[cfg(feature = "weak_default")]
-            if let Some(default_body) = &mut method.default {
-                let default_body_cleaned = rewrite_self_in_default_body(
-                    default_body,
-                    trait_name,
-                    macro_arg.namespace.as_deref(),
-                    &method_signatures,
+            if let Some(default_body) = method.default.as_mut().filter(|_| !macro_arg.checked) {
+                let mut opaque_body = default_body.clone();
+                substitute_self_types(None, Some(&mut opaque_body), &resolve_opaque);
+                let default_body_cleaned =
+                    rewrite_self_in_default_body(&opaque_body, trait_name, &method_signatures);
+                let mut weak_default_sig = extern_def_sig(ext_sig);
+                weak_default_sig.ident = extern_fn_name.clone();
+                let placement = macro_arg.placement.overridden_by(&placements[&index]);
+                let symbol = extern_fn_name.to_string();
+                visibility_items.push(placement.visibility_items(&quote! { #symbol }, &cfgs));
+                let placement = placement.attrs();
+                let default_call = wrap_call(
+                    macro_arg.unwind,
+                    &quote! { #mod_name },
+                    sig,
+                    quote! { #default_body_cleaned },
                 );
                 let weak_default_impl = quote! {
                     #[allow(non_snake_case)]
                     #[linkage = "weak"]
                     #[no_mangle]
-                    extern "Rust" #extern_fn_sig #default_body_cleaned
+                    #placement
+                    #weak_default_sig {
+                        #default_call
+                    }
                 };
 
-                let caller_args = extract_caller_args(sig)?;
+                // Opaque types are converted to and from their opaque storage,
+                // which is checked to fit the types of the implementation.
+                let is_opaque = |ty: &Type| {
+                    self_assoc_type(ty)
+                        .filter(|name| opaque_types.contains(name))
+                        .cloned()
+                };
+                let mut converted = false;
+                let mut caller_args = extract_caller_args(sig)?;
+                for (arg, input) in caller_args.iter_mut().zip(&sig.inputs) {
+                    let FnArg::Typed(input) = input else {
+                        continue;
+                    };
+                    if let Some(name) = is_opaque(&input.ty) {
+                        let binding = type_binding_trait_name(&name);
+                        *arg = parse_quote!(#types_path::#binding::wrap(#arg));
+                        converted = true;
+                    }
+                }
+                let mut call = quote! { #extern_fn_name ( #caller_args ) };
+                if let ReturnType::Type(_, ty) = &sig.output {
+                    if let Some(name) = is_opaque(ty) {
+                        let binding = type_binding_trait_name(&name);
+                        call = quote! { #types_path::#binding::unwrap(#call) };
+                        converted = true;
+                    }
+                }
+                if converted || sig.unsafety.is_some() {
+                    call = quote! { unsafe { #call } };
+                }
                 *default_body = syn::parse2(quote! {{
                     #weak_default_impl
 
-                    #extern_fn_name ( #caller_args )
+                    #call
                 }})?;
             }
 
+            // Keep the default implementations of checked interfaces in the
+            // trait the implementations forward to, so they are resolved here.
+            if let Some(default_body) = method.default.as_ref().filter(|_| macro_arg.checked) {
+                let mut default_sig = sig.clone();
+                default_sig.ident = default_fn_name(fn_name);
+                default_fns.push(quote! {
+                    #(#cfgs)*
+                    #[allow(deprecated)]
+                    #default_sig #default_body
+                });
+            }
+
+            // Forward the method of the proxy type to the linked implementation.
+            let mut proxy_sig = forwarding_sig(sig);
+            proxy_sig.abi = sig.abi.clone();
+            let proxy_args = extract_caller_args(sig)?;
+            proxy_items.push(quote! {
+                #(#cfgs)*
+                #allow_deprecated
+                #[inline]
+                #proxy_sig {
+                    unsafe { #mod_name :: #methods_mod :: #fn_name ( #proxy_args ) }
+                }
+            });
+
             if macro_arg.gen_caller {
                 let attrs = &method.attrs;
-                let caller_fn_sig = sig.clone();
-                let caller_args = extract_caller_args(sig)?;
+                let mut caller_fn_sig = forwarding_sig(ext_sig);
+                caller_fn_sig.abi = None;
+                let caller_args = if let Some(original_sig) = original_signatures.get(&index) {
+                    caller_fn_sig.inputs = opaque_sig(original_sig, false).inputs;
+                    lowered_caller_args(original_sig)?
+                } else {
+                    extract_caller_args(sig)?
+                };
                 callers.push(quote! {
                     #(#attrs)*
                     #[inline]
                     #vis #caller_fn_sig {
-                        unsafe { #mod_name :: #extern_fn_name ( #caller_args ) }
+                        unsafe { #mod_name :: #methods_mod :: #fn_name ( #caller_args ) }
                     }
                 })
             }
+
+            // The default body only forwards the arguments to the weak symbol.
+            #[cfg(feature = "weak_default")]
+            if method.default.is_some() && !macro_arg.checked {
+                method.sig = forwarding_sig(&method.sig);
+            }
+        }
+    }
+
+    // Declare the function returning the implemented version, and the version
+    // guard that every implementation must define with its version.
+    if let Some(version) = macro_arg.version {
+        let version_fn = version_fn_name();
+        if let Some(method) = ast.items.iter().find_map(|item| match item {
+            TraitItem::Fn(method) if method.sig.ident == version_fn => Some(method),
+            _ => None,
+        }) {
+            return Err(Error::new_spanned(
+                &method.sig.ident,
+                format!("`{version_fn}` is reserved in versioned interfaces"),
+            ));
         }
+        let extern_version_fn = extern_fn_name(symbol_ns.as_deref(), trait_name, &version_fn);
+        extern_fn_list.push(quote! {
+            pub fn #extern_version_fn() -> u32;
+        });
+        method_reexports.push(quote! {
+            pub use super::#extern_version_fn as #version_fn;
+        });
+        resolver_methods.push(quote! {
+            #[inline]
+            pub fn #version_fn(&self) -> u32 {
+                unsafe { #methods_mod::#version_fn() }
+            }
+        });
+
+        let version_guard_name = version_guard_name();
+        let latest_version_name = latest_version_name();
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #version_guard_name: u32;
+        ));
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #latest_version_name: u32 = #version;
+        ));
+    }
+
+    // Define the fingerprint checked when loading implementations from shared
+    // objects, and the type holding the loaded functions.
+    let mut dylib_support = None;
+    let mut plugin = None;
+    if macro_arg.dylib {
+        let fingerprint_fn = fingerprint_fn_name();
+        if let Some(method) = ast.items.iter().find_map(|item| match item {
+            TraitItem::Fn(method) if method.sig.ident == fingerprint_fn => Some(method),
+            _ => None,
+        }) {
+            return Err(Error::new_spanned(
+                &method.sig.ident,
+                format!("`{fingerprint_fn}` is reserved with the `dylib` option"),
+            ));
+        }
+        let fingerprint = fingerprint(fingerprint_parts.iter().map(String::as_str));
+        let fingerprint_const = fingerprint_const_name();
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #fingerprint_const: u64 = #fingerprint;
+        ));
+
+        let extern_fingerprint_fn =
+            extern_fn_name(symbol_ns.as_deref(), trait_name, &fingerprint_fn).to_string();
+        let plugin_name = plugin_type_name(trait_name);
+        let dylib_mod = dylib_mod_name();
+        let doc = format!(
+            " Implementation of [`{trait_name}`] loaded from a shared object, whose functions are called through it."
+        );
+        let message = format!("the shared object implements another definition of `{trait_name}`");
+        dylib_support = Some(dylib_support_items());
+        plugin = Some(quote! {
+            #[doc = #doc]
+            #[allow(dead_code)]
+            #[derive(Clone, Copy)]
+            #vis struct #plugin_name {
+                #(#plugin_fields,)*
+            }
+
+            #[allow(dead_code)]
+            impl #plugin_name {
+                /// Load the implementation from the shared object at `path`,
+                /// which is never unloaded.
+                ///
+                /// # Safety
+                ///
+                /// Loading the shared object runs its initialization code. Its
+                /// functions are only checked by the fingerprint of the
+                /// interface, so it must be built by the same compiler, with
+                /// the same definitions of the types used in the interface.
+                pub unsafe fn dlopen(
+                    path: &str,
+                ) -> ::core::result::Result<Self, ::std::string::String> {
+                    let handle = #mod_name::#dylib_mod::open(path)?;
+                    let fingerprint = ::core::mem::transmute::<
+                        *mut ::core::ffi::c_void,
+                        unsafe extern "Rust" fn() -> u64,
+                    >(#mod_name::#dylib_mod::symbol(handle, #extern_fingerprint_fn)?);
+                    if fingerprint() != #fingerprint {
+                        return ::core::result::Result::Err(::std::string::String::from(#message));
+                    }
+                    ::core::result::Result::Ok(Self {
+                        #(#plugin_loads,)*
+                    })
+                }
+
+                #(#plugin_methods)*
+            }
+        });
+    }
+
+    // Define the guards and the associated types for the proxy type.
+    for item in &ast.items {
+        if let TraitItem::Type(assoc) = item {
+            let name = &assoc.ident;
+            proxy_items.push(quote! { type #name = #types_path::#name; });
+        }
+    }
+    if let Some(version) = macro_arg.version {
+        let version_guard_name = version_guard_name();
+        proxy_items.push(quote! { const #version_guard_name: u32 = #version; });
+    }
+
+    // Enforce the unwind policy to match, as the one of the implementation is
+    // applied to the calls through the interface, and the one here to the
+    // default implementations. Propagating is the behavior without the option.
+    let (unwind_support, panic_type) = unwind_support_items(macro_arg.unwind, trait_name, vis);
+    if let Some(policy) = macro_arg
+        .unwind
+        .filter(|policy| *policy != UnwindPolicy::Propagate)
+    {
+        let unwind_guard_name = unwind_guard_name(policy.as_str());
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #unwind_guard_name: ();
+        ));
+        proxy_items.push(quote! { const #unwind_guard_name: () = (); });
+    }
+
+    // Enforce the implementations to be candidates selected by the same key,
+    // and reference the symbol exported by the selected one, so that linking
+    // none or several of them fails with an error naming the key.
+    let mut select_marker = None;
+    if let Some(key) = &macro_arg.select {
+        let select_guard_name = select_guard_name(&key.value());
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #select_guard_name: ();
+        ));
+        proxy_items.push(quote! { const #select_guard_name: () = (); });
+
+        let marker_name = select_marker_name(&key.value(), trait_name);
+        let marker_ref_name = select_marker_ref_name();
+        select_marker = Some(quote! {
+            extern "Rust" {
+                static #marker_name: u8;
+            }
+            #[used]
+            static #marker_ref_name: &u8 = unsafe { &#marker_name };
+        });
     }
 
     // Enforce no alias is used to implement an interface, as this makes it
     // possible to link the function called by `call_interface` to an
     // implementation with a different signature, which is extremely unsound.
-    let alias_guard_name = alias_guard_name(trait_name);
-    let alias_guard = parse_quote!(
-        #[allow(non_upper_case_globals)]
+    // `impl_interface` checks the name it is given against this one.
+    let alias_guard_name = alias_guard_name();
+    let trait_str = trait_name.to_string();
+    ast.items.push(parse_quote!(
         #[doc(hidden)]
-        const #alias_guard_name: () = ();
-    );
-    ast.items.push(alias_guard);
-
-    // Enforce namespace matching if a namespace is specified. No default value
-    // should be provided to ensure that `impl_interface` has a namespace
-    // specified when `def_interface` has one.
-    if let Some(ns) = &macro_arg.namespace {
-        let ns_guard_name = namespace_guard_name(ns);
-        let ns_guard = parse_quote!(
+        const #alias_guard_name: &'static str = #trait_str;
+    ));
+
+    // Enforce namespace matching, which `impl_interface` checks like the name.
+    let ns_guard_name = namespace_guard_name();
+    let ns_str = macro_arg.namespace.as_deref().unwrap_or_default();
+    ast.items.push(parse_quote!(
+        #[doc(hidden)]
+        const #ns_guard_name: &'static str = #ns_str;
+    ));
+
+    // Enforce `namespace = crate` to be given to `impl_interface` as well, and
+    // export the macro through which it finds the namespace, since it does not
+    // know the package defining the interface.
+    let mut namespace_macro = None;
+    if let Some(ns) = symbol_ns.as_deref().filter(|_| macro_arg.crate_namespace) {
+        let crate_ns_guard_name = crate_namespace_guard_name();
+        ast.items.push(parse_quote!(
             #[allow(non_upper_case_globals)]
             #[doc(hidden)]
-            const #ns_guard_name: ();
-        );
-        ast.items.push(ns_guard);
+            const #crate_ns_guard_name: ();
+        ));
+        proxy_items.push(quote! { const #crate_ns_guard_name: () = (); });
+
+        let crate_ns_macro = crate_namespace_macro_name(trait_name);
+        let ns_macro = namespace_macro_name();
+        namespace_macro = Some(quote! {
+            #[doc(hidden)]
+            #[macro_export]
+            macro_rules! #crate_ns_macro {
+                () => {
+                    #ns
+                };
+            }
+            pub use #crate_ns_macro as #ns_macro;
+        });
+    }
+
+    // Require the implementations to be checked, and export the macro passing
+    // the definition to them, which invokes `impl_interface` again with it.
+    let mut definition_macro = None;
+    if let Some(definition) = definition {
+        let checked_guard_name = checked_guard_name();
+        ast.items.push(parse_quote!(
+            #[allow(non_upper_case_globals)]
+            #[doc(hidden)]
+            const #checked_guard_name: ();
+        ));
+        proxy_items.push(quote! { const #checked_guard_name: () = (); });
+
+        // Bound associated types are known to the default implementations.
+        let defaults_trait = defaults_trait_name();
+        let bindings = macro_arg
+            .types
+            .iter()
+            .flatten()
+            .map(|(name, ty)| quote! { #name = #ty });
+        let trait_bound = quote! { #trait_name<#(#bindings),*> };
+
+        let macro_name = definition_macro_name(symbol_ns.as_deref(), trait_name);
+        let macro_alias = definition_macro_alias();
+        definition_macro = Some(quote! {
+            #[doc(hidden)]
+            #[macro_export]
+            macro_rules! #macro_name {
+                ([$($args:tt)*] $($item:tt)*) => {
+                    #[::crate_interface::impl_interface(__definition((#attr) #definition), $($args)*)]
+                    $($item)*
+                };
+            }
+            pub use #macro_name as #macro_alias;
+
+            pub trait #defaults_trait: #trait_bound {
+                #(#default_fns)*
+            }
+
+            impl<T: #trait_bound> #defaults_trait for T {}
+        });
     }
 
+    // The proxy type implements the trait by forwarding to whatever
+    // implementation is linked. The supertraits are not known to implement
+    // for it, so it is only generated for interfaces without supertraits.
+    let proxy = supertrait_resolvers.is_empty().then(|| {
+        let proxy_name = proxy_type_name(trait_name);
+        let doc = format!(
+            " Proxy of the linked implementation of [`{trait_name}`], which forwards every call to it."
+        );
+        quote! {
+            #[doc = #doc]
+            #[allow(dead_code)]
+            #[derive(Clone, Copy, Debug, Default)]
+            #vis struct #proxy_name;
+
+            #[allow(non_upper_case_globals)]
+            impl #trait_name for #proxy_name {
+                #(#proxy_items)*
+            }
+        }
+    });
+
     Ok(quote! {
         #ast
 
+        #proxy
+
+        #plugin
+
+        #panic_type
+
         #[doc(hidden)]
         #[allow(non_snake_case)]
         #vis mod #mod_name {
@@ -285,8 +1383,61 @@ pub fn def_interface(
             extern "Rust" {
                 #(#extern_fn_list)*
             }
+            #(#extern_blocks)*
+
+            #select_marker
+
+            #(#visibility_items)*
+
+            #alloc_support
+
+            #dylib_support
+
+            #unwind_support
+
+            #namespace_macro
+
+            #definition_macro
+
+            #types_mod
+
+            #(#trace_wrappers)*
+
+            pub mod #methods_mod {
+                #(#method_reexports)*
+            }
+
+            pub struct #resolver_name<__Next = ()>(pub #resolver_chain);
+
+            #[allow(dead_code)]
+            impl<__Next> #resolver_name<__Next> {
+                pub const fn #resolver_new(next: __Next) -> Self {
+                    Self(#resolver_chain_new)
+                }
+
+                #[inline]
+                pub fn #namespace_check(&self) -> &Self {
+                    self
+                }
+
+                #(#resolver_methods)*
+            }
+
+            impl<__Next> ::core::ops::Deref for #resolver_name<__Next> {
+                type Target = #resolver_chain;
+
+                fn deref(&self) -> &Self::Target {
+                    &self.0
+                }
+            }
         }
 
+        // Named after the trait, so `call_interface!` can go through it wherever
+        // the trait is imported or re-exported.
+        #[doc(hidden)]
+        #[allow(dead_code, non_upper_case_globals)]
+        #vis const #trait_name: #mod_name::#resolver_name = #mod_name::#resolver_name::#resolver_new(());
+
         #(#callers)*
     })
 }
@@ -0,0 +1,298 @@
+//! Checking implementations against the definition of checked interfaces,
+//! which `def_interface` passes to `impl_interface` through a generated macro.
+
+use std::collections::HashMap;
+
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, Error, FnArg, Ident, ImplItem, ItemImpl, ReturnType, Signature, TraitItem,
+    TraitItemFn, Type,
+};
+
+use crate::args::{DefInterfaceArgs, ImplInterfaceArgs, InterfaceDefinition, UnwindPolicy};
+use crate::errors::definition_mismatch_error;
+use crate::lowering::substitute_self_types;
+use crate::naming::{
+    default_fn_name, defaults_trait_name, definition_macro_alias, extern_fn_mod_path,
+    extract_caller_args,
+};
+
+/// Pass the implementation to the macro generated by `def_interface`, which
+/// invokes `impl_interface` again with the definition of the interface.
+pub fn definition_callback(ast: &ItemImpl, attr: TokenStream) -> Result<TokenStream, Error> {
+    let Some((_, path, _)) = &ast.trait_ else {
+        return Err(Error::new_spanned(ast, "expect a trait implementation"));
+    };
+    let mod_path = extern_fn_mod_path(path);
+    let macro_alias = definition_macro_alias();
+    Ok(quote! {
+        #mod_path::#macro_alias! { [#attr] #ast }
+    })
+}
+
+/// Describe the namespace given by the arguments, for the error messages.
+fn describe_namespace(namespace: Option<&str>, crate_namespace: bool) -> String {
+    match namespace {
+        Some(ns) => format!("`namespace = {ns}`"),
+        None if crate_namespace => "`namespace = crate`".into(),
+        None => "no namespace".into(),
+    }
+}
+
+/// Describe the unwind policy given by the arguments, for the error messages.
+fn describe_unwind(unwind: Option<UnwindPolicy>) -> &'static str {
+    unwind.unwrap_or(UnwindPolicy::Propagate).as_str()
+}
+
+/// Check the arguments of `impl_interface` that must match the ones of
+/// `def_interface`.
+fn check_args(
+    ast: &ItemImpl,
+    macro_arg: &ImplInterfaceArgs,
+    def_arg: &DefInterfaceArgs,
+    trait_name: &Ident,
+) -> Result<(), Error> {
+    let impl_path = &ast.trait_.as_ref().unwrap().1;
+
+    if macro_arg.namespace != def_arg.namespace
+        || macro_arg.crate_namespace != def_arg.crate_namespace
+    {
+        return Err(definition_mismatch_error(
+            impl_path,
+            format!(
+                "the implementation has {}, but the interface is defined with {}",
+                describe_namespace(macro_arg.namespace.as_deref(), macro_arg.crate_namespace),
+                describe_namespace(def_arg.namespace.as_deref(), def_arg.crate_namespace),
+            ),
+            trait_name,
+        ));
+    }
+
+    match (macro_arg.version, def_arg.version) {
+        (None, Some(latest)) => {
+            return Err(definition_mismatch_error(
+                impl_path,
+                format!("the interface is versioned, give the implemented version between 1 and {latest} with `version`"),
+                trait_name,
+            ));
+        }
+        (Some(_), None) => {
+            return Err(definition_mismatch_error(
+                impl_path,
+                "the interface is not versioned, remove the `version` of the implementation",
+                trait_name,
+            ));
+        }
+        (Some(version), Some(latest)) if version > latest => {
+            return Err(definition_mismatch_error(
+                impl_path,
+                format!("the implemented version {version} is newer than the latest version {latest} of the interface"),
+                trait_name,
+            ));
+        }
+        _ => {}
+    }
+
+    let unwind = describe_unwind(macro_arg.unwind);
+    let def_unwind = describe_unwind(def_arg.unwind);
+    if unwind != def_unwind {
+        return Err(definition_mismatch_error(
+            impl_path,
+            format!("the implementation has `unwind = {unwind}`, but the interface is defined with `unwind = {def_unwind}`"),
+            trait_name,
+        ));
+    }
+
+    if macro_arg.lower_impl_trait != def_arg.lower_impl_trait {
+        let (has, lacks) = if macro_arg.lower_impl_trait {
+            ("the implementation", "the interface")
+        } else {
+            ("the interface", "the implementation")
+        };
+        return Err(definition_mismatch_error(
+            impl_path,
+            format!("{has} has the `lower_impl_trait` option, but {lacks} does not"),
+            trait_name,
+        ));
+    }
+    Ok(())
+}
+
+/// The parts of a signature compared between the implementation and the
+/// definition, described as text, with the tokens to point the errors at.
+fn sig_parts(sig: &Signature) -> Vec<(String, TokenStream)> {
+    let describe = |present: bool, qualifier: &str| {
+        if present {
+            format!("`{qualifier}`")
+        } else {
+            format!("no `{qualifier}`")
+        }
+    };
+    let abi = &sig.abi;
+    let mut parts = vec![
+        (
+            describe(sig.asyncness.is_some(), "async"),
+            sig.ident.to_token_stream(),
+        ),
+        (
+            describe(sig.unsafety.is_some(), "unsafe"),
+            sig.ident.to_token_stream(),
+        ),
+        (
+            format!("the ABI `{}`", quote!(#abi)),
+            sig.ident.to_token_stream(),
+        ),
+        (
+            format!("{} arguments", sig.inputs.len()),
+            sig.inputs.to_token_stream(),
+        ),
+    ];
+    for (index, input) in sig.inputs.iter().enumerate() {
+        let part = match input {
+            FnArg::Receiver(receiver) => format!("the receiver `{}`", quote!(#receiver)),
+            FnArg::Typed(arg) => {
+                let ty = &arg.ty;
+                format!("argument {} of type `{}`", index + 1, quote!(#ty))
+            }
+        };
+        parts.push((part, input.to_token_stream()));
+    }
+    let output = match &sig.output {
+        ReturnType::Default => "the return type `()`".into(),
+        ReturnType::Type(_, ty) => format!("the return type `{}`", quote!(#ty)),
+    };
+    parts.push((output, sig.output.to_token_stream()));
+    parts
+}
+
+/// Compare the signature of an implemented method with the one in the
+/// definition, with the associated types of `Self` resolved to the ones bound
+/// by the implementation.
+fn check_sig(
+    sig: &Signature,
+    def_sig: &Signature,
+    resolve: &dyn Fn(&Ident) -> Option<Type>,
+) -> Result<(), Error> {
+    // The parts are described with the types resolved, and point to the
+    // signatures as written.
+    let parts = |sig: &Signature| {
+        let mut resolved = sig.clone();
+        substitute_self_types(Some(&mut resolved), None, resolve);
+        sig_parts(&resolved)
+            .into_iter()
+            .zip(sig_parts(sig))
+            .map(|((part, _), (_, tokens))| (part, tokens))
+            .collect::<Vec<_>>()
+    };
+
+    // The arguments are counted before they are compared, so the parts of
+    // signatures differing in length differ before either ends.
+    for ((part, tokens), (def_part, def_tokens)) in parts(sig).into_iter().zip(parts(def_sig)) {
+        if part != def_part {
+            return Err(definition_mismatch_error(
+                tokens,
+                format!("the implementation has {part}, but the definition has {def_part}"),
+                def_tokens,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check the implementation against the definition of the interface, and add
+/// the methods with default implementations it omits, which forward to the
+/// default implementations kept by `def_interface`.
+pub fn check_definition(
+    ast: &mut ItemImpl,
+    macro_arg: &ImplInterfaceArgs,
+    definition: &InterfaceDefinition,
+) -> Result<(), Error> {
+    let trait_name = &definition.item.ident;
+    check_args(ast, macro_arg, &definition.args, trait_name)?;
+
+    let mut def_methods: HashMap<String, Vec<&TraitItemFn>> = HashMap::new();
+    for item in &definition.item.items {
+        if let TraitItem::Fn(method) = item {
+            def_methods
+                .entry(method.sig.ident.to_string())
+                .or_default()
+                .push(method);
+        }
+    }
+
+    // The associated types bound by the implementation.
+    let assoc_types: Vec<(Ident, Type)> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Type(assoc) => Some((assoc.ident.clone(), assoc.ty.clone())),
+            _ => None,
+        })
+        .collect();
+    let resolve = |name: &Ident| {
+        assoc_types
+            .iter()
+            .find(|(assoc, _)| assoc == name)
+            .map(|(_, ty)| ty.clone())
+    };
+
+    let mut implemented = vec![];
+    for item in &ast.items {
+        let ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let fn_name = &method.sig.ident;
+        let Some(def_methods) = def_methods.get(&fn_name.to_string()) else {
+            return Err(definition_mismatch_error(
+                fn_name,
+                format!("`{fn_name}` is not a method of the interface `{trait_name}`"),
+                trait_name,
+            ));
+        };
+        // Methods configured differently may share the name, so any of them
+        // may be the one implemented.
+        let mut result = Ok(());
+        for def_method in def_methods {
+            result = check_sig(&method.sig, &def_method.sig, &resolve);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result?;
+        implemented.push(fn_name.to_string());
+    }
+
+    let mod_path = extern_fn_mod_path(&ast.trait_.as_ref().unwrap().1);
+    let defaults_trait = defaults_trait_name();
+    for item in &definition.item.items {
+        let TraitItem::Fn(method) = item else {
+            continue;
+        };
+        let sig = &method.sig;
+        if method.default.is_none() || implemented.contains(&sig.ident.to_string()) {
+            continue;
+        }
+        let cfgs = method
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg") || attr.path().is_ident("cfg_attr"));
+        let default_fn = default_fn_name(&sig.ident);
+        let args = extract_caller_args(sig)?;
+        let mut call = quote! { <Self as #mod_path::#defaults_trait>::#default_fn(#args) };
+        if sig.asyncness.is_some() {
+            call = quote! { #call.await };
+        }
+        if sig.unsafety.is_some() {
+            call = quote! { unsafe { #call } };
+        }
+        ast.items.push(parse_quote! {
+            #(#cfgs)*
+            #[allow(deprecated)]
+            #sig {
+                #call
+            }
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! Loading of interface implementations from shared objects, enabled by the
+//! `dylib` feature.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Attribute, Error, FnArg, Ident, Signature};
+
+use crate::lowering::forwarding_sig;
+use crate::naming::{dylib_mod_name, extract_caller_args};
+
+/// Compute the fingerprint of an interface from the textual representation of
+/// its parts, with the 64-bit FNV-1a hash. The parts are the name, namespace
+/// and version of the interface, and the tokens of its signatures, so a type
+/// used in them is only covered by the way it is spelled, not by its layout.
+pub fn fingerprint<'a>(parts: impl IntoIterator<Item = &'a str>) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        // Separate the parts, so they can not be shifted into each other.
+        for byte in part.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+/// Generate the module with the functions opening shared objects and resolving
+/// their symbols, to be placed in the module generated by `def_interface`.
+pub fn dylib_support_items() -> TokenStream {
+    let dylib_mod = dylib_mod_name();
+    quote! {
+        pub mod #dylib_mod {
+            use ::core::ffi::{c_char, c_int, c_void};
+            use ::std::ffi::{CStr, CString};
+            use ::std::string::{String, ToString};
+
+            // Before glibc 2.34, the functions are in `libdl` instead of `libc`.
+            #[cfg_attr(all(target_os = "linux", target_env = "gnu"), link(name = "dl"))]
+            extern "C" {
+                fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+                fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+                fn dlerror() -> *mut c_char;
+            }
+
+            const RTLD_NOW: c_int = 2;
+
+            unsafe fn last_error() -> String {
+                let error = dlerror();
+                if error.is_null() {
+                    String::from("unknown error")
+                } else {
+                    CStr::from_ptr(error).to_string_lossy().into_owned()
+                }
+            }
+
+            /// Open the shared object at `path`, which is never closed.
+            pub unsafe fn open(path: &str) -> Result<*mut c_void, String> {
+                let path = CString::new(path).map_err(|err| err.to_string())?;
+                let handle = dlopen(path.as_ptr(), RTLD_NOW);
+                if handle.is_null() {
+                    Err(last_error())
+                } else {
+                    Ok(handle)
+                }
+            }
+
+            /// Resolve the symbol named `name` in the shared object.
+            pub unsafe fn symbol(handle: *mut c_void, name: &str) -> Result<*mut c_void, String> {
+                let name = CString::new(name).map_err(|err| err.to_string())?;
+                let symbol = dlsym(handle, name.as_ptr());
+                if symbol.is_null() {
+                    Err(last_error())
+                } else {
+                    Ok(symbol)
+                }
+            }
+        }
+    }
+}
+
+/// Generate the field of the loaded function of an interface method in the
+/// plugin type, the expression loading it, and the method calling it.
+///
+/// The signature is the one of the extern function. The items are configured
+/// with `cfgs`, and the method is given `method_attrs` as well.
+pub fn plugin_method_items(
+    sig: &Signature,
+    mod_name: &Ident,
+    symbol: &Ident,
+    cfgs: &[Attribute],
+    method_attrs: &TokenStream,
+) -> Result<(TokenStream, TokenStream, TokenStream), Error> {
+    let fn_name = &sig.ident;
+    let dylib_mod = dylib_mod_name();
+
+    let lifetimes: Vec<_> = sig
+        .generics
+        .lifetimes()
+        .map(|param| &param.lifetime)
+        .collect();
+    let binder = (!lifetimes.is_empty()).then(|| quote! { for<#(#lifetimes),*> });
+    let arg_types = sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(arg) => Some(&arg.ty),
+        FnArg::Receiver(_) => None,
+    });
+    let abi = sig
+        .abi
+        .clone()
+        .unwrap_or_else(|| parse_quote!(extern "Rust"));
+    let output = &sig.output;
+    let fn_type = quote! { #binder unsafe #abi fn(#(#arg_types),*) #output };
+
+    let field = quote! {
+        #(#cfgs)*
+        #fn_name: #fn_type
+    };
+    let symbol = symbol.to_string();
+    let load = quote! {
+        #(#cfgs)*
+        #fn_name: ::core::mem::transmute::<*mut ::core::ffi::c_void, #fn_type>(
+            #mod_name::#dylib_mod::symbol(handle, #symbol)?,
+        )
+    };
+
+    // The plugin type is `Copy`, so the methods take it by value, which keeps
+    // the lifetimes of the signature as they are.
+    let mut method_sig = forwarding_sig(sig);
+    method_sig.abi = None;
+    method_sig.inputs.insert(0, parse_quote!(self));
+    let args = extract_caller_args(sig)?;
+    let method = quote! {
+        #(#cfgs)*
+        #method_attrs
+        #[inline]
+        pub #method_sig {
+            unsafe { (self.#fn_name)(#args) }
+        }
+    };
+    Ok((field, load, method))
+}
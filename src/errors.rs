@@ -1,6 +1,7 @@
 //! Error definitions for the crate interface.
 
-use syn::{Error, Generics, Ident, TraitItemFn};
+use quote::ToTokens;
+use syn::{Error, Ident, TraitItemFn};
 
 pub fn duplicate_arg_error(ident: &Ident) -> Error {
     Error::new_spanned(ident, format!("duplicate argument: {}", ident))
@@ -10,13 +11,20 @@ pub fn unknown_arg_error(ident: &Ident) -> Error {
     Error::new_spanned(ident, format!("unknown argument: {}", ident))
 }
 
-pub fn generic_not_allowed_error(generic: &Generics) -> Error {
+pub fn generic_not_allowed_error(generic: impl ToTokens) -> Error {
     Error::new_spanned(
         generic,
         "generic parameters are not allowed in crate_interface",
     )
 }
 
+pub fn impl_trait_not_allowed_error(impl_trait: impl ToTokens) -> Error {
+    Error::new_spanned(
+        impl_trait,
+        "`impl Trait` is not allowed in crate_interface, as interface functions are exported as non-generic extern symbols. Use `&dyn Trait` or `Box<dyn Trait>` instead, or enable the `lower_impl_trait` option to lower `impl Trait` arguments to `&dyn Trait` and returned `impl Trait` to `Box<dyn Trait>`",
+    )
+}
+
 #[cfg_attr(feature = "weak_default", allow(dead_code))]
 pub fn weak_default_required_error(method: &TraitItemFn) -> Error {
     let fn_name = &method.sig.ident;
@@ -28,3 +36,27 @@ pub fn weak_default_required_error(method: &TraitItemFn) -> Error {
         ),
     )
 }
+
+pub fn fallback_requires_weak_default_error(method: &TraitItemFn, reason: &str) -> Error {
+    let fn_name = &method.sig.ident;
+    Error::new_spanned(
+        method,
+        format!(
+            "method `{}` {}, so it needs a default implementation, which requires the `weak_default` feature",
+            fn_name, reason
+        ),
+    )
+}
+
+pub fn definition_mismatch_error(
+    tokens: impl ToTokens,
+    message: impl std::fmt::Display,
+    definition: impl ToTokens,
+) -> Error {
+    let mut error = Error::new_spanned(tokens, message);
+    error.combine(Error::new_spanned(
+        definition,
+        "the interface is defined here",
+    ));
+    error
+}
@@ -1,46 +1,279 @@
 //! The implementation of the [`crate::impl_interface`] attribute macro.
 
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
-use syn::{parse_quote, Error, ImplItem, ItemImpl, Type};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{parse_quote, Attribute, Error, FnArg, Ident, ImplItem, ItemImpl, ReturnType, Type};
 
-use crate::args::ImplInterfaceArgs;
-use crate::naming::{alias_guard_name, extern_fn_name, extract_caller_args, namespace_guard_name};
+use crate::args::{ImplInterfaceArgs, SymbolPlacement, UnwindPolicy};
+use crate::definition::check_definition;
+use crate::errors::unknown_arg_error;
+use crate::lowering::{
+    extern_def_sig, forwarding_sig, lower_async_body, lower_async_sig, lower_boxed_return_body,
+    lower_impl_trait_sig, self_assoc_type, strip_receiver, substitute_self_types,
+};
+use crate::naming::{
+    alias_guard_name, checked_guard_name, clock_fn_name, crate_namespace_guard_name,
+    extern_fn_mod_path, extern_fn_name, extract_caller_args, fingerprint_const_name,
+    fingerprint_fn_name, implemented_guard_name, latest_version_name, methods_mod_name,
+    namespace_guard_name, namespace_macro_name, required_methods_guard_name, select_env_var_name,
+    select_guard_name, select_marker_name, stats_entries_name, stats_registry_name,
+    type_binding_trait_name, types_mod_name, unwind_guard_name, version_fn_name,
+    version_guard_name, STATS_SECTION_NAME,
+};
+use crate::unwind::wrap_call;
 use crate::validator::validate_fn_signature;
 
+/// Parse and strip the `#[interface(...)]` attributes of a method, which
+/// override the placement of its exported function.
+fn take_method_placement(attrs: &mut Vec<Attribute>) -> Result<SymbolPlacement, Error> {
+    let mut placement = SymbolPlacement::default();
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("interface"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let ident = meta.path.require_ident()?;
+            if placement.parse_arg(ident, meta.input)? {
+                Ok(())
+            } else {
+                Err(unknown_arg_error(ident))
+            }
+        })?;
+    }
+    attrs.retain(|attr| !attr.path().is_ident("interface"));
+    Ok(placement)
+}
+
 /// The implementation of the [`crate::impl_interface`] attribute macro.
 pub fn impl_interface(
     mut ast: ItemImpl,
     macro_arg: ImplInterfaceArgs,
 ) -> Result<TokenStream, Error> {
-    let trait_name = if let Some((_, path, _)) = &ast.trait_ {
-        &path.segments.last().unwrap().ident
+    // The methods written in the implementation, which are the ones it
+    // implements in versioned interfaces, unlike the default implementations
+    // added to checked implementations.
+    let written_methods: Vec<Ident> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Fn(method) => Some(method.sig.ident.clone()),
+            _ => None,
+        })
+        .collect();
+
+    // Check the implementation of checked interfaces against the definition
+    // passed back with it.
+    if let (Some(definition), Some(_)) = (&macro_arg.definition, &ast.trait_) {
+        check_definition(&mut ast, &macro_arg, definition)?;
+    }
+
+    let (trait_name, mod_path) = if let Some((_, path, _)) = &ast.trait_ {
+        (
+            &path.segments.last().unwrap().ident,
+            extern_fn_mod_path(path),
+        )
     } else {
         return Err(Error::new_spanned(ast, "expect a trait implementation"));
     };
+    let mod_path = quote! { #mod_path };
+    // The name of the symbol exporting a function of the interface. With
+    // `namespace = crate`, the namespace is only known to the macro exported by
+    // `def_interface`.
+    let export_name = |fn_name: &Ident| {
+        if macro_arg.crate_namespace {
+            let ns_macro = namespace_macro_name();
+            let suffix = format!("_{trait_name}_{fn_name}");
+            quote! { ::core::concat!("__", #mod_path::#ns_macro!(), #suffix) }
+        } else {
+            let name =
+                extern_fn_name(macro_arg.namespace.as_deref(), trait_name, fn_name).to_string();
+            quote! { #name }
+        }
+    };
     let impl_name = if let Type::Path(path) = &ast.self_ty.as_ref() {
         path.path.get_ident().unwrap()
     } else {
         return Err(Error::new_spanned(ast, "expect a trait implementation"));
     };
 
+    let trait_path = ast.trait_.as_ref().unwrap().1.clone();
+    let types_mod = types_mod_name();
+    let types_path = quote! { #mod_path::#types_mod };
+    let methods_mod = methods_mod_name();
+    // The associated types bound by the implementation. Only the positions
+    // spelled `Self::Name` are converted to the types bound in `def_interface`
+    // at the extern boundary, as the concrete types may appear elsewhere.
+    let assoc_types: Vec<Ident> = ast
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Type(assoc) => Some(assoc.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    let assoc_type_of = |ty: &Type| self_assoc_type(ty).cloned();
+    // Only the candidate matching the value of the build key is exported, along
+    // with the marker symbol the definition references. The key is read from
+    // the environment when expanding, and again with `option_env!` to rebuild
+    // the crate when it changes.
+    let mut select_items = quote! {};
+    let selected = if let Some((key, value)) = &macro_arg.select {
+        let env_var = select_env_var_name(&key.value());
+        let marker_name = select_marker_name(&key.value(), trait_name).to_string();
+        select_items = quote! {
+            const _: ::core::option::Option<&str> = ::core::option_env!(#env_var);
+        };
+        match std::env::var(&env_var) {
+            Ok(current) if current == value.value() => {
+                select_items.extend(quote! {
+                    const _: () = {
+                        #[used]
+                        #[export_name = #marker_name]
+                        static SELECTED: u8 = 0;
+                    };
+                });
+                true
+            }
+            Ok(_) => false,
+            Err(_) => {
+                return Err(Error::new_spanned(
+                    key,
+                    format!(
+                        "the build key `{}` is not set, set the `{env_var}` environment variable to select one of its implementations",
+                        key.value()
+                    ),
+                ));
+            }
+        }
+    } else {
+        true
+    };
+
+    let registry_name = stats_registry_name(impl_name, trait_name);
+    let clock_fn = clock_fn_name();
+    let mut stats_entries = vec![];
+    let mut visibility_items = vec![];
+
     for item in &mut ast.items {
         if let ImplItem::Fn(method) = item {
+            // Lower `async` methods to ones returning boxed futures
+            if lower_async_sig(&mut method.sig, &mod_path)? {
+                method.block = lower_async_body(&method.block, &mod_path);
+            }
+            // Lower `impl Trait` types to trait objects if enabled
+            if macro_arg.lower_impl_trait && lower_impl_trait_sig(&mut method.sig, &mod_path)? {
+                method.block = lower_boxed_return_body(&method.block, &mod_path);
+            }
+
+            let placement = macro_arg
+                .placement
+                .overridden_by(&take_method_placement(&mut method.attrs)?);
+
             let (attrs, vis, sig, stmts) =
                 (&method.attrs, &method.vis, &method.sig, &method.block.stmts);
             let fn_name = &sig.ident;
-            let extern_fn_name =
-                extern_fn_name(macro_arg.namespace.as_deref(), trait_name, fn_name).to_string();
+            let export_name = export_name(fn_name);
 
             // Validate signature: reject generic parameters and receivers
-            validate_fn_signature(sig)?;
+            validate_fn_signature(sig, macro_arg.instance.is_some())?;
+            if !selected {
+                continue;
+            }
+            visibility_items.push(placement.visibility_items(&export_name, &method.attrs));
+            let placement = placement.attrs();
+
+            let mut new_sig = extern_def_sig(&forwarding_sig(&strip_receiver(sig)));
+            // The name is given a call-site span, so the lints skip it.
+            let shim_name =
+                extern_fn_name(macro_arg.namespace.as_deref(), trait_name, fn_name).to_string();
+            new_sig.ident = format_ident!("{}", shim_name);
+
+            let mut args = extract_caller_args(sig)?;
+            let plain_args = args.clone();
+            let mut converted = false;
+            // Pass the associated types across the extern boundary as the types
+            // bound in `def_interface`, like the opaque storage.
+            for (arg, input) in args.iter_mut().zip(new_sig.inputs.iter_mut()) {
+                let FnArg::Typed(input) = input else {
+                    continue;
+                };
+                let (ty, unwrap) = match &mut *input.ty {
+                    Type::Reference(reference) if reference.mutability.is_some() => {
+                        (&mut *reference.elem, quote!(unwrap_mut))
+                    }
+                    Type::Reference(reference) => (&mut *reference.elem, quote!(unwrap_ref)),
+                    ty => (ty, quote!(unwrap)),
+                };
+                if let Some(name) = assoc_type_of(ty) {
+                    let binding = type_binding_trait_name(&name);
+                    *ty = parse_quote!(#types_path::#name);
+                    *arg = parse_quote!(#types_path::#binding::#unwrap(#arg));
+                    converted = true;
+                }
+            }
+            let mut wrap_ret = None;
+            if let ReturnType::Type(_, ty) = &mut new_sig.output {
+                if let Some(name) = assoc_type_of(ty) {
+                    **ty = parse_quote!(#types_path::#name);
+                    wrap_ret = Some(type_binding_trait_name(&name));
+                    converted = true;
+                }
+            }
+            // `Self` is not available in the nested function, so its
+            // associated types are spelled out.
+            substitute_self_types(Some(&mut new_sig), None, &|name| {
+                Some(parse_quote!(<#impl_name as #trait_path>::#name))
+            });
+
+            // Check the exported function against the declaration in the
+            // interface, which rejects the associated types spelled by their
+            // concrete types instead of `Self::Name`.
+            let signature_check = (!assoc_types.is_empty()).then(|| {
+                let mut check_sig = new_sig.clone();
+                check_sig.ident = format_ident!("__check_signature");
+                check_sig.abi = None;
+                check_sig.unsafety = None;
+                quote_spanned! { sig.span() =>
+                    #[allow(dead_code, deprecated)]
+                    #check_sig {
+                        unsafe { #mod_path::#methods_mod::#fn_name(#plain_args) }
+                    }
+                }
+            });
 
-            let mut new_sig = sig.clone();
-            new_sig.ident = format_ident!("{}", extern_fn_name);
+            // Methods with receivers are called on the registered instance.
+            let mut call_impl = match (sig.receiver(), &macro_arg.instance) {
+                (Some(_), Some(instance)) => quote! { #impl_name::#fn_name( &#instance, #args ) },
+                _ => quote! { #impl_name::#fn_name( #args ) },
+            };
+            if let Some(binding) = wrap_ret {
+                call_impl = quote! { #types_path::#binding::wrap(#call_impl) };
+            }
+            if converted || sig.unsafety.is_some() {
+                call_impl = quote! { unsafe { #call_impl } };
+            }
 
-            let args = extract_caller_args(sig)?;
+            // Count the call and accumulate its duration if stats are enabled.
+            if macro_arg.stats {
+                let index = stats_entries.len();
+                stats_entries.push(fn_name.to_string());
+                call_impl = quote! {
+                    extern "Rust" {
+                        fn #clock_fn() -> u64;
+                    }
+                    let start = unsafe { #clock_fn() };
+                    let ret = #call_impl;
+                    let elapsed = unsafe { #clock_fn() }.wrapping_sub(start);
+                    let stats = &#registry_name[#index];
+                    stats[0].fetch_add(1, ::core::sync::atomic::Ordering::Relaxed);
+                    stats[1].fetch_add(elapsed as _, ::core::sync::atomic::Ordering::Relaxed);
+                    ret
+                };
+            }
 
-            let call_impl = quote! { #impl_name::#fn_name( #args ) };
+            // Apply the unwind policy to everything the implementation runs.
+            call_impl = wrap_call(macro_arg.unwind, &mod_path, sig, call_impl);
 
             let item: TokenStream = quote! {
                 #[inline]
@@ -49,11 +282,15 @@ pub fn impl_interface(
                 #sig
                 {
                     {
+                        // The method may be deprecated in the interface.
+                        #[allow(deprecated)]
                         #[inline]
-                        #[export_name = #extern_fn_name]
-                        extern "Rust" #new_sig {
+                        #[export_name = #export_name]
+                        #placement
+                        #new_sig {
                             #call_impl
                         }
+                        #signature_check
                     }
                     #(#stmts)*
                 }
@@ -62,17 +299,256 @@ pub fn impl_interface(
         }
     }
 
-    // generate alias guard to prevent aliasing of trait names
-    let alias_guard_name = alias_guard_name(trait_name);
-    let alias_guard = parse_quote!(const #alias_guard_name: () = (););
-    ast.items.push(alias_guard);
+    // Check the associated types against the concrete types bound to them in
+    // `def_interface`, or that they fit in the opaque storage.
+    let type_checks = assoc_types.iter().map(|name| {
+        let binding = type_binding_trait_name(name);
+        quote! {
+            const _: () =
+                <#types_path::#name as #types_path::#binding<<#impl_name as #trait_path>::#name>>::FITS;
+        }
+    });
+    let type_checks = quote! { #(#type_checks)* };
 
-    // generate namespace guard to enforce namespace matching
-    if let Some(ns) = macro_arg.namespace {
-        let ns_guard_name = namespace_guard_name(&ns);
-        let ns_guard = parse_quote!(const #ns_guard_name: () = (););
-        ast.items.push(ns_guard);
+    // check the alias guard to prevent aliasing of trait names, and the
+    // namespace guard to enforce namespace matching
+    let alias_guard_name = alias_guard_name();
+    let ns_guard_name = namespace_guard_name();
+    let trait_str = trait_name.to_string();
+    let ns_str = macro_arg.namespace.as_deref().unwrap_or_default();
+    let alias_message = format!(
+        "the interface must be implemented by its name, not through the alias `{trait_str}`"
+    );
+    let ns_message = match &macro_arg.namespace {
+        Some(ns) => format!("the interface is not defined with `namespace = {ns}`"),
+        None => "the interface is defined with a namespace, which must be given".into(),
+    };
+    let guard_checks = quote! {
+        const _: () = {
+            const fn eq(a: &str, b: &str) -> bool {
+                let (a, b) = (a.as_bytes(), b.as_bytes());
+                if a.len() != b.len() {
+                    return false;
+                }
+                let mut i = 0;
+                while i < a.len() {
+                    if a[i] != b[i] {
+                        return false;
+                    }
+                    i += 1;
+                }
+                true
+            }
+            ::core::assert!(eq(<#impl_name as #trait_path>::#alias_guard_name, #trait_str), #alias_message);
+            ::core::assert!(eq(<#impl_name as #trait_path>::#ns_guard_name, #ns_str), #ns_message);
+        };
+    };
+
+    // generate unwind guard to enforce the unwind policy matching
+    if let Some(policy) = macro_arg
+        .unwind
+        .filter(|policy| *policy != UnwindPolicy::Propagate)
+    {
+        let unwind_guard_name = unwind_guard_name(policy.as_str());
+        ast.items
+            .push(parse_quote!(const #unwind_guard_name: () = ();));
+    }
+
+    // generate checked guard, as the implementation is checked
+    if macro_arg.definition.is_some() {
+        let checked_guard_name = checked_guard_name();
+        ast.items
+            .push(parse_quote!(const #checked_guard_name: () = ();));
+    }
+
+    // generate select guard to enforce the build key matching
+    if let Some((key, _)) = &macro_arg.select {
+        let select_guard_name = select_guard_name(&key.value());
+        ast.items
+            .push(parse_quote!(const #select_guard_name: () = ();));
+    }
+
+    // generate crate namespace guard to enforce `namespace = crate` matching
+    if macro_arg.crate_namespace {
+        let crate_ns_guard_name = crate_namespace_guard_name();
+        ast.items
+            .push(parse_quote!(const #crate_ns_guard_name: () = ();));
     }
 
-    Ok(quote! { #ast })
+    // Define the implemented version, which must not be newer than the latest
+    // version of the interface, and export it.
+    let mut version_items = quote! {};
+    if let Some(version) = macro_arg.version {
+        let version_guard_name = version_guard_name();
+        let latest_version_name = latest_version_name();
+        ast.items
+            .push(parse_quote!(const #version_guard_name: u32 = #version;));
+
+        // Mark the implemented methods, which the methods added in the
+        // implemented version (or an older one) must be.
+        let implemented_guards: Vec<ImplItem> = ast
+            .items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Fn(method) if written_methods.contains(&method.sig.ident) => {
+                    let cfgs = method.attrs.iter().filter(|attr| {
+                        attr.path().is_ident("cfg") || attr.path().is_ident("cfg_attr")
+                    });
+                    let implemented_guard = implemented_guard_name(&method.sig.ident);
+                    Some(parse_quote!(
+                        #(#cfgs)*
+                        const #implemented_guard: bool = true;
+                    ))
+                }
+                _ => None,
+            })
+            .collect();
+        ast.items.extend(implemented_guards);
+        let required_guard = required_methods_guard_name();
+
+        let extern_version_fn = export_name(&version_fn_name());
+        let message =
+            format!("the implemented version of `{trait_name}` is newer than its definition");
+        let export = selected.then(|| {
+            quote! {
+                #[export_name = #extern_version_fn]
+                extern "Rust" fn interface_version() -> u32 {
+                    #version
+                }
+            }
+        });
+        version_items = quote! {
+            const _: () = {
+                ::core::assert!(
+                    #version <= <#impl_name as #trait_path>::#latest_version_name,
+                    #message
+                );
+                let () = <#impl_name as #trait_path>::#required_guard;
+
+                #export
+            };
+        };
+    }
+
+    // Export the fingerprint of the interface, which is checked when loading
+    // the implementation from a shared object.
+    let mut fingerprint_items = quote! {};
+    if macro_arg.export_dynamic && selected {
+        let fingerprint_const = fingerprint_const_name();
+        let extern_fingerprint_fn = export_name(&fingerprint_fn_name());
+        fingerprint_items = quote! {
+            const _: () = {
+                #[export_name = #extern_fingerprint_fn]
+                extern "Rust" fn interface_fingerprint() -> u64 {
+                    <#impl_name as #trait_path>::#fingerprint_const
+                }
+            };
+        };
+    }
+
+    if !macro_arg.stats {
+        return Ok(quote! {
+            #ast
+            #guard_checks
+            #version_items
+            #type_checks
+            #select_items
+            #fingerprint_items
+            #(#visibility_items)*
+        });
+    }
+
+    // Generate the statistics registry, and register its counters in the
+    // statistics section, where `interface_stats!` enumerates them. The
+    // counters are 64-bit where the target has 64-bit atomics, and wrap at
+    // the pointer width otherwise.
+    let entries_name = stats_entries_name(impl_name, trait_name);
+    let count = stats_entries.len();
+    let registry = |cfg: TokenStream, atomic: TokenStream| {
+        let counters = stats_entries
+            .iter()
+            .map(|_| quote! { [#atomic::new(0), #atomic::new(0)] });
+        let entries = stats_entries.iter().enumerate().map(|(index, fn_str)| {
+            quote! { (#trait_str, #fn_str, &#registry_name[#index]) }
+        });
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            #cfg
+            static #registry_name: [[#atomic; 2]; #count] = [#(#counters),*];
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals)]
+            #cfg
+            #[used]
+            #[link_section = #STATS_SECTION_NAME]
+            static #entries_name: [(&str, &str, &[#atomic; 2]); #count] = [#(#entries),*];
+        }
+    };
+    let registry_64 = registry(
+        quote! { #[cfg(target_has_atomic = "64")] },
+        quote! { ::core::sync::atomic::AtomicU64 },
+    );
+    let registry_ptr = registry(
+        quote! { #[cfg(not(target_has_atomic = "64"))] },
+        quote! { ::core::sync::atomic::AtomicUsize },
+    );
+    Ok(quote! {
+        #ast
+        #guard_checks
+        #version_items
+        #type_checks
+        #select_items
+        #fingerprint_items
+        #(#visibility_items)*
+
+        #registry_64
+        #registry_ptr
+        #[cfg(any(
+            target_vendor = "apple",
+            target_os = "windows",
+            target_os = "uefi",
+            target_family = "wasm"
+        ))]
+        ::core::compile_error!("`stats` is only supported on ELF targets");
+    })
+}
+
+/// Generate the expansion of `interface_stats!`, iterating over the entries
+/// registered in the statistics section by every implementation with the
+/// `stats` option.
+pub fn interface_stats() -> TokenStream {
+    let start = format!("__start_{STATS_SECTION_NAME}");
+    let stop = format!("__stop_{STATS_SECTION_NAME}");
+    quote! {{
+        #[cfg(target_has_atomic = "64")]
+        type Counter = ::core::sync::atomic::AtomicU64;
+        #[cfg(not(target_has_atomic = "64"))]
+        type Counter = ::core::sync::atomic::AtomicUsize;
+        type Entry = (&'static str, &'static str, &'static [Counter; 2]);
+
+        // Keep the section, and its bounds, defined without any entries.
+        #[used]
+        #[link_section = #STATS_SECTION_NAME]
+        static EMPTY: [Entry; 0] = [];
+
+        extern "Rust" {
+            #[link_name = #start]
+            static START: Entry;
+            #[link_name = #stop]
+            static STOP: Entry;
+        }
+        let start = unsafe { ::core::ptr::addr_of!(START) };
+        let stop = unsafe { ::core::ptr::addr_of!(STOP) };
+        let len = (stop as usize - start as usize) / ::core::mem::size_of::<Entry>();
+        unsafe { ::core::slice::from_raw_parts(start, len) }
+            .iter()
+            .map(|(trait_name, method, counters)| {
+                (
+                    *trait_name,
+                    *method,
+                    counters[0].load(::core::sync::atomic::Ordering::Relaxed) as u64,
+                    counters[1].load(::core::sync::atomic::Ordering::Relaxed) as u64,
+                )
+            })
+    }}
 }
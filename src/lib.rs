@@ -4,17 +4,21 @@
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
-use syn::{parse::Error, parse_macro_input, ItemImpl, ItemTrait, PathArguments, PathSegment};
+use syn::{parse::Error, parse_macro_input, ItemImpl, ItemTrait};
 
 mod args;
 mod def_interface;
+mod definition;
+mod dylib;
 mod errors;
 mod impl_interface;
+mod lowering;
 mod naming;
+mod unwind;
 mod validator;
 
-use args::{CallInterface, DefInterfaceArgs, ImplInterfaceArgs};
-use naming::{extern_fn_mod_name, extern_fn_name};
+use args::{BindInterface, CallInterface, DefInterfaceArgs, ImplInterfaceArgs};
+use naming::{bound_type_name, namespace_check_fn_name};
 
 fn compiler_error(err: Error) -> TokenStream {
     err.to_compile_error().into()
@@ -36,12 +40,309 @@ fn compiler_error(err: Error) -> TokenStream {
 /// It is also possible to generate calling helper functions for each interface
 /// function by enabling the `gen_caller` option.
 ///
+/// ## Crate Namespaces
+///
+/// With `namespace = crate`, the symbols are namespaced with the name of the
+/// package defining the interface, read from `CARGO_PKG_NAME`, so interfaces
+/// of the same name in different crates do not collide. The implementation
+/// must be marked with `namespace = crate` too, and [`call_interface!`] is
+/// used without a namespace. Older compilers, like Rust 1.68, can not resolve
+/// the hidden macro in the crate defining the interface, so it can only be
+/// implemented in other crates there.
+///
+/// ## Tracing
+///
+/// With the `trace` option, every call through the interface (the generated
+/// calling helper functions, [`call_interface!`] and the `Self::` references in
+/// default implementations) is wrapped with calls to the `on_enter` and
+/// `on_exit` hooks of the `InterfaceTraceIf` interface, which receive the trait
+/// name, the method name and the namespace (empty if none). The application
+/// defines and implements the hook interface itself:
+///
+/// ```rust,ignore
+/// #[def_interface]
+/// pub trait InterfaceTraceIf {
+///     fn on_enter(trait_name: &'static str, method: &'static str, namespace: &'static str);
+///     fn on_exit(trait_name: &'static str, method: &'static str, namespace: &'static str);
+/// }
+/// ```
+///
+/// ## Async Methods
+///
+/// With the `alloc` feature, interface methods can be `async`. The futures
+/// can not cross crates as opaque types, so they are boxed behind the extern
+/// symbols: `async fn f(..) -> T` is lowered to a function returning
+/// `Pin<Box<dyn Future<Output = T> + Send + 'static>>`, both in the trait and
+/// in the implementation. Consequently, the futures must be `Send`, and the
+/// arguments of `async` methods must not borrow anything.
+///
+/// ## Supertraits
+///
+/// An interface may inherit other interfaces as supertraits. Methods of the
+/// supertrait interfaces can then be called through the subtrait with
+/// [`call_interface!`], and implementing the subtrait requires the supertrait
+/// interfaces to be implemented for the same type:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface]
+/// trait DeviceIf {
+///     fn device_id() -> u32;
+/// }
+///
+/// #[def_interface]
+/// trait NetIf: DeviceIf {
+///     fn mtu() -> usize;
+/// }
+///
+/// struct NetImpl;
+///
+/// #[impl_interface] // error: `DeviceIf` is not implemented for `NetImpl`
+/// impl NetIf for NetImpl {
+///     fn mtu() -> usize { 1500 }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Supertraits must be interfaces themselves, and must be named by a path
+/// through which their generated modules are reachable, just like the path
+/// passed to [`call_interface!`].
+///
+/// ## Associated Types
+///
+/// The implementation is not visible to the crate defining the interface, so
+/// the associated types of an interface are bound to concrete types in the
+/// definition with the `types(Name = Type, ...)` option. `Self::Name` in the
+/// signatures and default implementations is resolved to the bound type, and
+/// the implementation must bind the associated type to the same type:
+///
+/// ```rust
+/// # use crate_interface::*;
+/// #[derive(Debug, PartialEq)]
+/// pub struct HalError;
+///
+/// #[def_interface(types(Error = HalError))]
+/// trait HalIf {
+///     type Error;
+///     fn init() -> Result<(), Self::Error>;
+/// }
+///
+/// struct HalImpl;
+///
+/// #[impl_interface]
+/// impl HalIf for HalImpl {
+///     type Error = HalError; // must be `HalError`
+///     fn init() -> Result<(), Self::Error> {
+///         Err(HalError)
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(call_interface!(HalIf::init()), Err(HalError));
+/// # }
+/// ```
+///
+/// ## Opaque Types
+///
+/// An associated type declared as `type Name: Sized = opaque!(size = N, align = N);`
+/// is bound by the implementation instead, and is passed across the interface
+/// in opaque storage of the given layout. `impl_interface` checks at compile
+/// time that the type of the implementation fits in it. Opaque types can only
+/// be passed by value or by reference, or returned by value, and the values
+/// are not dropped with the storage. The storage is neither `Send` nor `Sync`.
+///
+/// Only the arguments and return values spelled `Self::Name` are converted to
+/// and from the storage, so the implementation must spell them the same way
+/// as the interface, not by the concrete type:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface]
+/// trait TicketIf {
+///     type Ticket: Sized = opaque!(size = 8, align = 8);
+///     fn issue(seq: u64) -> Self::Ticket;
+/// }
+///
+/// struct TicketImpl;
+///
+/// #[impl_interface]
+/// impl TicketIf for TicketImpl {
+///     type Ticket = u64;
+///     fn issue(seq: u64) -> u64 { // error: expected `Self::Ticket`
+///         seq
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ## Versioning
+///
+/// With the `version = N` option, the interface evolves in compatible
+/// revisions. Methods added after the first version are tagged with
+/// `#[since(version)]`, and are filled for the implementations of older
+/// versions from their default implementations, or from "unsupported" stubs
+/// that panic if they have none. Both require the `weak_default` feature. The
+/// implementations of newer versions must implement the methods without
+/// default implementations.
+///
+/// The implemented version can be queried with
+/// `call_interface!(MyIf::interface_version())`, so `interface_version` can
+/// not be the name of a method of a versioned interface.
+///
+/// ## Conditional Methods
+///
+/// The `#[cfg]` and `#[cfg_attr]` attributes of a method are copied to every
+/// item generated for it, so methods can be conditionally compiled, or even
+/// declared with different signatures under mutually exclusive configurations.
+/// The implementation must be configured the same way.
+///
+/// ## Deprecation
+///
+/// `#[deprecated]` on a method is carried over to the generated calling helper
+/// function and to the function that [`call_interface!`] expands to. With
+/// `#[interface(replaced_by = new_method)]`, a retired method is given a
+/// default implementation forwarding to `new_method`, which also requires the
+/// `weak_default` feature.
+///
+/// ## Instance Methods
+///
+/// With the `instance` option, methods can take `&self` receivers, so the
+/// implementation can keep its state in a `static` of the implementing type,
+/// given by the `instance` option of [`impl_interface`](macro@crate::impl_interface).
+/// The receiver is not passed through the interface, and the methods are
+/// called like the other ones, with [`call_interface!`] or the calling helper
+/// functions:
+///
+/// ```rust
+/// # use crate_interface::*;
+/// # use core::sync::atomic::{AtomicU32, Ordering};
+/// #[def_interface(instance)]
+/// trait CounterIf {
+///     fn next(&self) -> u32;
+/// }
+///
+/// struct Counter(AtomicU32);
+///
+/// static COUNTER: Counter = Counter(AtomicU32::new(0));
+///
+/// #[impl_interface(instance = COUNTER)]
+/// impl CounterIf for Counter {
+///     fn next(&self) -> u32 {
+///         self.0.fetch_add(1, Ordering::Relaxed)
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(call_interface!(CounterIf::next()), 0);
+/// assert_eq!(call_interface!(CounterIf::next()), 1);
+/// # }
+/// ```
+///
+/// Only `&self` receivers are allowed, and the methods with receivers can not
+/// have default implementations. Lifetimes in the return type elided to the
+/// one of the receiver are `'static`.
+///
+/// ## Proxy Type
+///
+/// A public zero-sized `MyIfProxy` type implementing the trait is generated
+/// beside it, forwarding every method to the linked implementation, so the
+/// interface can be passed to generic code bounded by the trait:
+///
+/// ```rust
+/// # use crate_interface::*;
+/// #[def_interface]
+/// pub trait ClockIf {
+///     fn now() -> u64;
+/// }
+///
+/// fn elapsed<C: ClockIf>(start: u64) -> u64 {
+///     C::now() - start
+/// }
+///
+/// struct ClockImpl;
+///
+/// #[impl_interface]
+/// impl ClockIf for ClockImpl {
+///     fn now() -> u64 {
+///         42
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_eq!(elapsed::<ClockIfProxy>(40), 2);
+/// # }
+/// ```
+///
+/// The proxy reports the latest version of a versioned interface, and is not
+/// generated for interfaces with supertraits.
+///
+/// ## Symbol Placement
+///
+/// The `link_section = "..."` option places the weak default implementations
+/// in the given linker section, which a method can override with
+/// `#[interface(link_section = "...")]`. The `visibility` option sets their
+/// symbol visibility, as described for
+/// [`impl_interface`](macro@crate::impl_interface).
+///
+/// ## Shared Objects
+///
+/// With the `dylib` option, which requires the `dylib` feature and `std`, a
+/// `MyIfPlugin` type is generated as well, whose `dlopen` function loads an
+/// implementation from a shared object built with the `export_dynamic` option
+/// of [`impl_interface`](macro@crate::impl_interface), and whose methods call
+/// the loaded functions. The fingerprint of the interface, computed from its
+/// signatures, is checked when loading it. The `interface_fingerprint` method
+/// name is reserved with the option. The other interfaces are not affected by
+/// the feature, so `no_std` crates can still define them.
+///
+/// ## Selected Implementations
+///
+/// With `select = "key"`, the implementation is one of several candidates
+/// selected by a build key, which must all be marked with the same key. The
+/// definition references the marker symbol exported by the selected one, so
+/// that selecting none fails to link. See
+/// [`impl_interface`](macro@crate::impl_interface) for the details.
+///
+/// ## Unwinding
+///
+/// The `unwind = abort|propagate|catch` option decides what happens when the
+/// implementation (or a default implementation) panics. `propagate`, the
+/// behavior without the option, unwinds into the caller, and `abort` aborts the
+/// process instead. `catch`, which requires `std`, catches the panic: methods
+/// returning a `Result` return it as an error converted from the generated
+/// `MyIfPanic` type, and the other methods pass it to the `on_panic` hook of the
+/// `InterfacePanicIf` interface, which the application defines and implements
+/// like the tracing hooks:
+///
+/// ```rust,ignore
+/// #[def_interface]
+/// pub trait InterfacePanicIf {
+///     fn on_panic(trait_name: &'static str, method: &'static str, message: &str) -> !;
+/// }
+/// ```
+///
+/// Whether a method returns a `Result` is told by the last segment of the path
+/// of its return type, so aliases named otherwise go to the hook. The policy
+/// of [`impl_interface`](macro@crate::impl_interface) must match.
+///
+/// ## Checked Implementations
+///
+/// With the `checked` option, a hidden macro carrying the definition of the
+/// interface is generated, and the implementations must be marked with
+/// `checked` too. [`impl_interface`](macro@crate::impl_interface) then expands
+/// through the macro, and checks the implementation against the definition.
+/// The default implementations are kept in the crate defining the interface,
+/// and the implementations omitting them forward to them, so they need neither
+/// weak symbols nor the `weak_default` feature, and the same goes for
+/// `replaced_by` methods and the methods added in later versions.
+///
 /// ## Restrictions
 ///
 /// ### No Receivers
 ///
 /// Methods with receivers (`self`, `&self`, `&mut self`) are not
-/// allowed. Only associated functions (static methods) are supported:
+/// allowed. Only associated functions (static methods) are supported, unless
+/// `&self` methods are enabled with the `instance` option:
 ///
 /// ```rust,compile_fail
 /// # use crate_interface::*;
@@ -54,8 +355,8 @@ fn compiler_error(err: Error) -> TokenStream {
 /// ### No Generic Parameters
 ///
 /// Generic parameters are not supported. Interface functions cannot
-/// have generic type parameters, lifetime parameters, or const generic
-/// parameters:
+/// have generic type parameters or const generic parameters, but lifetime
+/// parameters are allowed:
 ///
 /// ```rust,compile_fail
 /// # use crate_interface::*;
@@ -64,12 +365,68 @@ fn compiler_error(err: Error) -> TokenStream {
 ///     fn foo<T>(x: T); // error: generic parameters are not allowed
 /// }
 /// ```
+///
+/// ### No `impl Trait`
+///
+/// `impl Trait` types are generic in disguise, so they are not supported in
+/// arguments or return types either:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface]
+/// trait MyIf {
+///     fn foo(f: impl Fn(u32)); // error: `impl Trait` is not allowed
+/// }
+/// ```
+///
+/// ### No `deref` Methods
+///
+/// `call_interface!` resolves the methods of supertrait interfaces by
+/// dereferencing, so a method named `deref` would be shadowed and is rejected:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface]
+/// trait MyIf {
+///     fn deref() -> u32; // error: `deref` is reserved
+/// }
+/// ```
+///
+/// With the `lower_impl_trait` option, which must also be given to
+/// [`impl_interface`](macro@crate::impl_interface), `impl Trait` arguments are
+/// lowered to `&dyn Trait`, and a returned `impl Trait` is lowered to
+/// `Box<dyn Trait>` (which requires the `alloc` feature). The generated calling
+/// helper functions still accept `impl Trait` arguments, while callers using
+/// [`call_interface!`] pass trait object references:
+///
+/// ```rust
+/// # use crate_interface::*;
+/// #[def_interface(gen_caller, lower_impl_trait)]
+/// trait VisitIf {
+///     fn visit(f: impl Fn(u32)); // lowered to `fn visit(f: &dyn Fn(u32))`
+/// }
+///
+/// struct VisitImpl;
+///
+/// #[impl_interface(lower_impl_trait)]
+/// impl VisitIf for VisitImpl {
+///     fn visit(f: impl Fn(u32)) {
+///         f(42);
+///     }
+/// }
+///
+/// # fn main() {
+/// visit(|x| assert_eq!(x, 42));
+/// call_interface!(VisitIf::visit(&|x| assert_eq!(x, 42)));
+/// # }
+/// ```
 #[proc_macro_attribute]
 pub fn def_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr_tokens = attr.clone().into();
     let macro_arg = syn::parse_macro_input!(attr as DefInterfaceArgs);
     let ast = syn::parse_macro_input!(item as ItemTrait);
 
-    def_interface::def_interface(ast, macro_arg)
+    def_interface::def_interface(ast, macro_arg, attr_tokens)
         .map(Into::into)
         .unwrap_or_else(compiler_error)
 }
@@ -85,6 +442,119 @@ pub fn def_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// See the [crate-level documentation](crate) for more details.
 ///
+/// ## Call Statistics
+///
+/// With the `stats` option, every call through the interface to a method
+/// implemented in the block is counted, and its duration is accumulated, as
+/// measured by the `now_nanos` function of the `InterfaceClockIf` interface.
+/// The application defines and implements the clock interface itself:
+///
+/// ```rust,ignore
+/// #[def_interface]
+/// pub trait InterfaceClockIf {
+///     fn now_nanos() -> u64;
+/// }
+/// ```
+///
+/// The statistics of every implementation with the option are enumerated
+/// together with [`interface_stats!`], which yields a
+/// `(trait, method, calls, total nanoseconds)` tuple for each method. They are
+/// registered in the `crate_interface_stats` linker section, so the option is
+/// only supported on ELF targets. The counters are `AtomicU64`, or
+/// `AtomicUsize` wrapping at the pointer width on targets without 64-bit
+/// atomics.
+///
+/// ## Symbol Placement
+///
+/// The `link_section = "..."` option places the exported functions in the
+/// given linker section, like `.text.boot` for the implementations used in
+/// early boot. It can be overridden for a method with
+/// `#[interface(link_section = "...")]`:
+///
+/// ```rust,ignore
+/// #[impl_interface(link_section = ".text.boot")]
+/// impl BootIf for BootImpl {
+///     #[interface(link_section = ".text.boot.console")]
+///     fn early_putchar(c: u8) {
+///         // ...
+///     }
+/// }
+/// ```
+///
+/// The `visibility = hidden|protected|default` option, also accepted per
+/// method, sets the ELF visibility of the exported functions with a
+/// `.hidden` or `.protected` directive in `global_asm!`. `hidden` and
+/// `protected` are compile errors on the targets not using ELF, like Windows,
+/// Apple platforms or WebAssembly, and `default` is the visibility without the
+/// option. Hidden functions can not be loaded with `export_dynamic`.
+///
+/// ## Exporting to Shared Objects
+///
+/// With the `export_dynamic` option, which requires the `dylib` feature and an
+/// interface defined with the `dylib` option, the fingerprint of the interface
+/// is exported along with the functions, so the implementation can be loaded
+/// from a shared object by the plugin type of the interface.
+///
+/// ## Selecting Implementations
+///
+/// With `select = "key", value = "value"`, the implementation is one of the
+/// candidates selected by the build key of an interface defined with
+/// `select = "key"`, and its symbols are only exported if the key has the
+/// given value. The key is read from the `CRATE_INTERFACE_SELECT_<KEY>`
+/// environment variable when the crate is built, with the key in upper case
+/// and other characters than ASCII letters and digits replaced by `_`, and it
+/// is an error if the variable is not set. It can be set in the `[env]`
+/// section of `.cargo/config.toml`. Selecting by `--cfg` is not supported, as
+/// the macros can not read the cfgs of the crate they expand in:
+///
+/// ```rust,ignore
+/// #[def_interface(select = "board")]
+/// trait BoardIf {
+///     // ...
+/// }
+///
+/// // Exported when built with `CRATE_INTERFACE_SELECT_BOARD=qemu-virt`.
+/// #[impl_interface(select = "board", value = "qemu-virt")]
+/// impl BoardIf for QemuVirt {
+///     // ...
+/// }
+/// ```
+///
+/// The other candidates still implement the trait, but are not linked to the
+/// interface. The selected one exports the marker symbol
+/// `__crate_interface_select_<key>_<Trait>` referenced by the definition, so
+/// if no candidate matches, linking fails with the marker undefined, even with
+/// `weak_default`. If more than one matches in a crate, the marker is defined
+/// twice, which fails to compile. Candidates in different crates must have
+/// different values, as the linker only reports the duplicate symbols of the
+/// crates it links entirely.
+///
+/// ## Checked Implementations
+///
+/// The implementations of interfaces defined with the `checked` option must be
+/// marked with `checked`, and are compared with the definition: the options
+/// must match it, every method must be one of the interface with the same
+/// signature, and the omitted methods with default implementations are added.
+/// The errors point at both the implementation and the definition:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface(checked)]
+/// trait MyIf {
+///     fn foo() -> u32;
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface(checked)]
+/// impl MyIf for MyImpl {
+///     fn foo() -> u64 { // error: the definition has the return type `u32`
+///         0
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
 /// ## Restrictions
 ///
 /// ### No Alias
@@ -127,10 +597,94 @@ pub fn def_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// ### No Unwind Policy Mismatch
+///
+/// The `unwind` policy, if it is not `propagate`, must match the one given when
+/// defining the interface:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface(unwind = abort)]
+/// trait MyIf {
+///     fn foo();
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface] // error: missing the `abort` unwind policy
+/// impl MyIf for MyImpl {
+///     fn foo() {}
+/// }
+/// ```
+///
+/// ### No Unchecked Implementation
+///
+/// The implementation of a checked interface must be checked:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface(checked)]
+/// trait MyIf {
+///     fn foo();
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface] // error: missing the `checked` option
+/// impl MyIf for MyImpl {
+///     fn foo() {}
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ### No Version Mismatch
+///
+/// The implementation of a versioned interface must specify the version it
+/// implements with the `version = N` option, which must not be newer than the
+/// version of the interface:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface(version = 1)]
+/// trait MyIf {
+///     fn foo();
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface(version = 2)] // error: the implemented version is newer
+/// impl MyIf for MyImpl {
+///     fn foo() {}
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The methods added in the implemented version or an older one must be
+/// implemented, unless they have a default implementation:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface(checked, version = 2)]
+/// trait MyIf {
+///     fn foo();
+///     #[since(2)]
+///     fn bar();
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface(checked, version = 2)] // error: `MyIf::bar` must be implemented
+/// impl MyIf for MyImpl {
+///     fn foo() {}
+/// }
+/// # fn main() {}
+/// ```
+///
 /// ### No Receivers
 ///
 /// Methods with receivers (`self`, `&self`, `&mut self`) are not
-/// allowed in the implementation either:
+/// allowed in the implementation either, unless `&self` methods are called on
+/// the instance given by the `instance` option:
 ///
 /// ```rust,compile_fail
 /// # use crate_interface::*;
@@ -165,19 +719,103 @@ pub fn def_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro_attribute]
 pub fn impl_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr_tokens = attr.clone().into();
     let arg = syn::parse_macro_input!(attr as ImplInterfaceArgs);
     let ast = syn::parse_macro_input!(item as ItemImpl);
 
+    // Checked implementations are expanded again with the definition.
+    if arg.checked && arg.definition.is_none() {
+        return definition::definition_callback(&ast, attr_tokens)
+            .map(Into::into)
+            .unwrap_or_else(compiler_error);
+    }
     impl_interface::impl_interface(ast, arg)
         .map(Into::into)
         .unwrap_or_else(compiler_error)
 }
 
+/// Enumerate the call statistics collected by every interface implementation
+/// with the `stats` option, linked into the program.
+///
+/// It evaluates to an iterator of `(trait, method, calls, total nanoseconds)`
+/// tuples, one for each method implemented in the blocks with the option. The
+/// statistics are registered in a linker section, so it is only supported on
+/// ELF targets.
+///
+/// See the [`stats` option of `impl_interface`](macro@impl_interface#call-statistics)
+/// for more details.
+#[proc_macro]
+pub fn interface_stats(item: TokenStream) -> TokenStream {
+    if !item.is_empty() {
+        return compiler_error(Error::new(
+            Span::call_site(),
+            "`interface_stats!` takes no arguments",
+        ));
+    }
+    impl_interface::interface_stats().into()
+}
+
 /// Call a function in a crate interface.
 ///
 /// It is not necessary to call it in the same crate as the implementation, but
 /// it is required that these crates are linked together.
 ///
+/// The trait can be named through any import or re-export of it, since the
+/// call goes through a hidden constant named after the trait.
+///
+/// Calls to `unsafe fn` methods must be wrapped in an `unsafe` block, like the
+/// calls to the methods themselves:
+///
+/// ```rust,compile_fail
+/// # use crate_interface::*;
+/// #[def_interface]
+/// trait MyIf {
+///     unsafe fn read(ptr: *const u8) -> u8;
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface]
+/// impl MyIf for MyImpl {
+///     unsafe fn read(ptr: *const u8) -> u8 {
+///         unsafe { *ptr }
+///     }
+/// }
+///
+/// # fn main() {
+/// let byte = 42;
+/// call_interface!(MyIf::read(&byte)); // error: requires an `unsafe` block
+/// # }
+/// ```
+///
+/// Interfaces defined with `crate_interface_lite` have no such constant, so
+/// they must be called with the `call_interface!` of `crate_interface_lite`,
+/// even if they are implemented with `crate_interface`:
+///
+/// ```rust,compile_fail
+/// crate_interface_lite::def_interface!(
+///     trait LiteIf {
+///         fn foo() -> u32;
+///     }
+/// );
+///
+/// struct MyImpl;
+///
+/// #[crate_interface::impl_interface]
+/// impl LiteIf for MyImpl {
+///     fn foo() -> u32 {
+///         42
+///     }
+/// }
+///
+/// # fn main() {
+/// crate_interface::call_interface!(LiteIf::foo()); // error: `LiteIf` is not a value
+/// # }
+/// ```
+///
+/// With the `bound,` prefix, the call is dispatched statically to the
+/// implementation bound with [`bind!`], which can be inlined.
+///
 /// See the [crate-level documentation](crate) for more details.
 #[proc_macro]
 pub fn call_interface(item: TokenStream) -> TokenStream {
@@ -188,17 +826,102 @@ pub fn call_interface(item: TokenStream) -> TokenStream {
     if path.len() < 2 {
         compiler_error(Error::new(Span::call_site(), "expect `Trait::func`"));
     }
-    let fn_name = path.pop().unwrap();
-    let trait_name = path.pop().unwrap();
-    let extern_fn_name = extern_fn_name(
-        call.namespace.as_deref(),
-        &trait_name.value().ident,
-        &fn_name.value().ident,
-    );
+    let fn_name = path.pop().unwrap().into_value().ident;
+    let trait_name = path.pop().unwrap().into_value().ident;
+    let namespace_check = namespace_check_fn_name(call.namespace.as_deref(), &trait_name);
 
-    path.push_value(PathSegment {
-        ident: extern_fn_mod_name(&trait_name.value().ident),
-        arguments: PathArguments::None,
-    });
-    quote! { unsafe { #path :: #extern_fn_name( #args ) } }.into()
+    // Go through the constant named after the trait, which is imported or
+    // re-exported along with it. Methods inherited from supertrait interfaces
+    // are resolved through it as well.
+    // The call is only unsafe if the method is, which the caller must wrap in
+    // an `unsafe` block. The method is looked up on the dereferenced
+    // resolver, so that the ones implemented for references (such as
+    // `Clone::clone`) do not shadow the interface methods.
+    //
+    // With `bound`, the call is dispatched statically to the implementation
+    // bound with `bind!` instead, after checking the namespace the same way.
+    if call.bound {
+        let bound_name = bound_type_name(&trait_name);
+        path.push(trait_name.into());
+        return quote! {{
+            let _ = #path.#namespace_check();
+            <#bound_name as #path>::#fn_name( #args )
+        }}
+        .into();
+    }
+    path.push(trait_name.into());
+    quote! { (*#path.#namespace_check()).#fn_name( #args ) }.into()
+}
+
+/// Bind the implementation of a crate interface, so it can be dispatched to
+/// statically.
+///
+/// `bind!(MyIf => MyImpl)` defines a type alias named `MyIfBound` of `MyImpl`,
+/// which must be the implementation linked to the interface. Calls made with
+/// `call_interface!(bound, MyIf::foo())` where the alias is in scope then go
+/// to the implementation directly instead of through the exported symbol, and
+/// can be inlined. A visibility can be given before the trait, like
+/// `bind!(pub MyIf => MyImpl)`.
+///
+/// The binding is meant to be declared once by the crate that links the
+/// implementation, usually the final binary. Other crates keep calling the
+/// interface through the exported symbols, without `bound`:
+///
+/// ```rust
+/// # use crate_interface::*;
+/// #[def_interface]
+/// trait MyIf {
+///     fn foo() -> u32;
+/// }
+///
+/// struct MyImpl;
+///
+/// #[impl_interface]
+/// impl MyIf for MyImpl {
+///     fn foo() -> u32 {
+///         42
+///     }
+/// }
+///
+/// bind!(MyIf => MyImpl);
+///
+/// # fn main() {
+/// assert_eq!(call_interface!(bound, MyIf::foo()), 42); // `MyImpl::foo`
+/// assert_eq!(call_interface!(MyIf::foo()), 42); // `__MyIf_foo`
+/// # }
+/// ```
+///
+/// Only the methods of the interface itself are dispatched statically. The
+/// methods inherited from supertrait interfaces are called through the
+/// supertraits, which are bound on their own.
+///
+/// `bind!` only checks that the bound type implements the interface. Since
+/// every implementation exports the symbols of the interface, any other one
+/// linked with it fails to link with duplicate symbols, unless it is a
+/// candidate that is not selected.
+#[proc_macro]
+pub fn bind(item: TokenStream) -> TokenStream {
+    let BindInterface {
+        vis,
+        trait_path,
+        ty,
+    } = parse_macro_input!(item as BindInterface);
+    let Some(trait_name) = trait_path.segments.last().map(|seg| &seg.ident) else {
+        return compiler_error(Error::new(Span::call_site(), "expect `Trait => Type`"));
+    };
+    let bound_name = bound_type_name(trait_name);
+    let doc = format!(" The implementation of [`{trait_name}`] bound with `bind!`.");
+
+    quote! {
+        #[doc = #doc]
+        #[allow(dead_code)]
+        #vis type #bound_name = #ty;
+
+        // Check that the bound type implements the interface.
+        const _: () = {
+            fn implements<T: #trait_path>() {}
+            let _ = implements::<#ty>;
+        };
+    }
+    .into()
 }
@@ -0,0 +1,351 @@
+//! Lowering of method signatures that cannot cross the extern boundary as-is.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, punctuated::Punctuated, token::Comma, visit::Visit, visit_mut::VisitMut, Abi,
+    Block, Error, Expr, FnArg, Ident, Lifetime, Pat, Path, QSelf, ReturnType, Signature, Type,
+    TypePath, TypeReference,
+};
+
+use crate::naming::{
+    box_fn_name, box_future_fn_name, box_future_type_name, box_type_name, extract_caller_args,
+};
+
+/// Lower the function signature to the one of a function that only forwards
+/// its arguments, by dropping the `mut` bindings of the arguments.
+pub fn forwarding_sig(sig: &Signature) -> Signature {
+    let mut sig = sig.clone();
+    for arg in &mut sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            if let Pat::Ident(pat) = &mut *arg.pat {
+                pat.mutability = None;
+            }
+        }
+    }
+    sig
+}
+
+/// Lower the signature of a method with a `&self` receiver, which is called on
+/// the registered instance, to the one of a function without it.
+///
+/// The elided lifetimes in the return type are the one of the receiver, and
+/// the instance is a `static`, so they are lowered to `'static`.
+pub fn strip_receiver(sig: &Signature) -> Signature {
+    /// Visitor that makes elided lifetimes `'static`.
+    struct StaticLifetimes;
+
+    impl VisitMut for StaticLifetimes {
+        fn visit_type_reference_mut(&mut self, reference: &mut TypeReference) {
+            syn::visit_mut::visit_type_reference_mut(self, reference);
+            reference
+                .lifetime
+                .get_or_insert_with(|| parse_quote!('static));
+        }
+
+        fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+            if lifetime.ident == "_" {
+                *lifetime = parse_quote!('static);
+            }
+        }
+    }
+
+    let mut sig = sig.clone();
+    if sig.receiver().is_none() {
+        return sig;
+    }
+    sig.inputs = sig
+        .inputs
+        .into_iter()
+        .filter(|arg| matches!(arg, FnArg::Typed(_)))
+        .collect();
+    if let ReturnType::Type(_, ty) = &mut sig.output {
+        StaticLifetimes.visit_type_mut(ty);
+    }
+    sig
+}
+
+/// Lower the function signature to the declaration of the extern function in
+/// an `extern` block, which can have neither qualifiers nor patterns.
+///
+/// Returns the ABI of the `extern` block along with the declaration, which is
+/// `extern "Rust"` unless the interface function specifies one.
+pub fn extern_decl_sig(sig: &Signature) -> (Abi, Signature) {
+    let mut decl = forwarding_sig(sig);
+    let abi = decl
+        .abi
+        .take()
+        .unwrap_or_else(|| parse_quote! { extern "Rust" });
+    decl.unsafety = None;
+    (abi, decl)
+}
+
+/// Lower the function signature to the definition of the extern function,
+/// which is `extern "Rust"` unless the interface function specifies an ABI.
+pub fn extern_def_sig(sig: &Signature) -> Signature {
+    let mut def = sig.clone();
+    def.abi
+        .get_or_insert_with(|| parse_quote! { extern "Rust" });
+    def
+}
+
+/// Find the associated type of `Self` at the front of the path, like `Name` in
+/// `Self::Name` or `<Self as Trait>::Name`, returning its name and the number
+/// of segments it takes.
+fn self_assoc_type_prefix<'a>(qself: Option<&QSelf>, path: &'a Path) -> Option<(&'a Ident, usize)> {
+    let position = match qself {
+        Some(qself) if qself.position > 0 => {
+            match &*qself.ty {
+                Type::Path(ty) if ty.qself.is_none() && ty.path.is_ident("Self") => {}
+                _ => return None,
+            }
+            qself.position
+        }
+        Some(_) => return None,
+        None => {
+            if path.leading_colon.is_some() || path.segments.first()?.ident != "Self" {
+                return None;
+            }
+            1
+        }
+    };
+    let name = &path.segments.iter().nth(position)?.ident;
+    Some((name, position + 1))
+}
+
+/// Get the name of the associated type if the type is exactly `Self::Name` or
+/// `<Self as Trait>::Name`.
+pub fn self_assoc_type(ty: &Type) -> Option<&Ident> {
+    let Type::Path(TypePath { qself, path }) = ty else {
+        return None;
+    };
+    match self_assoc_type_prefix(qself.as_ref(), path) {
+        Some((name, len)) if len == path.segments.len() => Some(name),
+        _ => None,
+    }
+}
+
+/// Find the first associated type of `Self` in the type, among the given
+/// names, including the type itself.
+pub fn find_self_type<'a>(ty: &'a Type, names: &[Ident]) -> Option<&'a Type> {
+    /// Visitor that records the first matching associated type.
+    struct SelfTypeFinder<'a, 'b> {
+        names: &'b [Ident],
+        found: Option<&'a Type>,
+    }
+
+    impl<'a> Visit<'a> for SelfTypeFinder<'a, '_> {
+        fn visit_type(&mut self, ty: &'a Type) {
+            if self_assoc_type(ty).map_or(false, |name| self.names.contains(name)) {
+                self.found.get_or_insert(ty);
+            }
+            syn::visit::visit_type(self, ty);
+        }
+    }
+
+    let mut finder = SelfTypeFinder { names, found: None };
+    finder.visit_type(ty);
+    finder.found
+}
+
+/// Substitute the `Self::Name` (or `<Self as Trait>::Name`) associated types in
+/// the signature and the default body of an interface method with the concrete
+/// types given by `resolve`, so the method can be lowered to items outside of
+/// the trait, where `Self` is not available.
+///
+/// Associated types are also substituted in the paths of expressions like
+/// `Self::Name::new()`. Types for which `resolve` returns `None` are kept.
+pub fn substitute_self_types(
+    sig: Option<&mut Signature>,
+    body: Option<&mut Block>,
+    resolve: &dyn Fn(&Ident) -> Option<Type>,
+) {
+    /// Visitor that substitutes the associated types of `Self`.
+    struct SelfTypeSubst<'a> {
+        resolve: &'a dyn Fn(&Ident) -> Option<Type>,
+    }
+
+    impl VisitMut for SelfTypeSubst<'_> {
+        fn visit_type_mut(&mut self, ty: &mut Type) {
+            syn::visit_mut::visit_type_mut(self, ty);
+            if let Some(concrete) = self_assoc_type(ty).and_then(self.resolve) {
+                *ty = concrete;
+            }
+        }
+
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            syn::visit_mut::visit_expr_mut(self, expr);
+            let Expr::Path(expr) = expr else {
+                return;
+            };
+            let Some((name, len)) = self_assoc_type_prefix(expr.qself.as_ref(), &expr.path) else {
+                return;
+            };
+            if len == expr.path.segments.len() {
+                return;
+            }
+            let Some(concrete) = (self.resolve)(name) else {
+                return;
+            };
+            // `Self::Name::item` becomes `<Concrete>::item`
+            let rest = expr.path.segments.iter().skip(len).cloned().collect();
+            expr.qself = Some(QSelf {
+                lt_token: Default::default(),
+                ty: Box::new(concrete),
+                position: 0,
+                as_token: None,
+                gt_token: Default::default(),
+            });
+            expr.path = Path {
+                leading_colon: Some(Default::default()),
+                segments: rest,
+            };
+        }
+    }
+
+    let mut subst = SelfTypeSubst { resolve };
+    if let Some(sig) = sig {
+        subst.visit_signature_mut(sig);
+    }
+    if let Some(body) = body {
+        subst.visit_block_mut(body);
+    }
+}
+
+/// Lower an `async fn` signature to a plain function returning a boxed future.
+///
+/// `mod_path` is the path to the module generated by `def_interface`, which
+/// provides the boxed future type. Returns `Ok(false)` if the function is not
+/// `async`.
+///
+/// Returns `Err(Error)` with a compile error if the `alloc` feature is not
+/// enabled.
+pub fn lower_async_sig(sig: &mut Signature, mod_path: &TokenStream) -> Result<bool, Error> {
+    let Some(asyncness) = sig.asyncness.take() else {
+        return Ok(false);
+    };
+    if cfg!(not(feature = "alloc")) {
+        return Err(Error::new_spanned(
+            asyncness,
+            "async methods are boxed behind the extern symbol, which requires the `alloc` feature of crate_interface",
+        ));
+    }
+
+    let output = match &sig.output {
+        ReturnType::Default => quote! { () },
+        ReturnType::Type(_, ty) => quote! { #ty },
+    };
+    let box_future = box_future_type_name();
+    sig.output = parse_quote! { -> #mod_path::#box_future<#output> };
+    Ok(true)
+}
+
+/// Lower the body of an `async fn` to one that boxes the future, to match the
+/// signature lowered by [`lower_async_sig`].
+pub fn lower_async_body(body: &Block, mod_path: &TokenStream) -> Block {
+    let box_future = box_future_fn_name();
+    parse_quote! {{
+        #mod_path::#box_future(async move #body)
+    }}
+}
+
+/// Lower `impl Trait` types in the function signature to trait objects.
+///
+/// Arguments of type `impl Trait` are lowered to `&dyn Trait`, and a returned
+/// `impl Trait` is lowered to `Box<dyn Trait>`, using the box type provided by
+/// the module generated by `def_interface` at `mod_path`. `impl Trait` types
+/// nested in other types are left as-is, to be rejected by the validator.
+///
+/// Returns `Ok(true)` if the return type is lowered, in which case the body
+/// must be lowered with [`lower_boxed_return_body`].
+///
+/// Returns `Err(Error)` with a compile error if the return type needs to be
+/// boxed but the `alloc` feature is not enabled.
+pub fn lower_impl_trait_sig(sig: &mut Signature, mod_path: &TokenStream) -> Result<bool, Error> {
+    for arg in &mut sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            if let Type::ImplTrait(impl_trait) = &*arg.ty {
+                let bounds = &impl_trait.bounds;
+                *arg.ty = parse_quote! { &(dyn #bounds) };
+            }
+        }
+    }
+
+    let ReturnType::Type(_, ty) = &mut sig.output else {
+        return Ok(false);
+    };
+    let Type::ImplTrait(impl_trait) = &**ty else {
+        return Ok(false);
+    };
+    if cfg!(not(feature = "alloc")) {
+        return Err(Error::new_spanned(
+            impl_trait,
+            "returned `impl Trait` is lowered to `Box<dyn Trait>`, which requires the `alloc` feature of crate_interface",
+        ));
+    }
+    let bounds = &impl_trait.bounds;
+    let box_type = box_type_name();
+    **ty = parse_quote! { #mod_path::#box_type<dyn #bounds> };
+    Ok(true)
+}
+
+/// Lower the function body to box the returned value, to match the signature
+/// lowered by [`lower_impl_trait_sig`].
+pub fn lower_boxed_return_body(body: &Block, mod_path: &TokenStream) -> Block {
+    let box_fn = box_fn_name();
+    parse_quote! {{
+        #mod_path::#box_fn(#body)
+    }}
+}
+
+/// Extract the argument list from the original function signature, before
+/// lowering with [`lower_impl_trait_sig`], to be used by a caller that calls
+/// the lowered function with the original arguments.
+pub fn lowered_caller_args(original_sig: &Signature) -> Result<Punctuated<Expr, Comma>, Error> {
+    let mut args = extract_caller_args(original_sig)?;
+    let typed_args = original_sig.inputs.iter().filter_map(|arg| match arg {
+        FnArg::Typed(arg) => Some(arg),
+        FnArg::Receiver(_) => None,
+    });
+    for (arg, typed_arg) in args.iter_mut().zip(typed_args) {
+        if let Type::ImplTrait(_) = &*typed_arg.ty {
+            *arg = parse_quote! { &#arg };
+        }
+    }
+    Ok(args)
+}
+
+/// Generate the items that support the signatures lowered to boxed types, to
+/// be placed in the module generated by `def_interface`.
+pub fn alloc_support_items() -> TokenStream {
+    let box_type = box_type_name();
+    let box_fn = box_fn_name();
+    let box_future_type = box_future_type_name();
+    let box_future_fn = box_future_fn_name();
+    quote! {
+        extern crate alloc as __alloc;
+
+        /// The box returned by lowered `impl Trait` return types.
+        pub type #box_type<T> = __alloc::boxed::Box<T>;
+
+        /// Box the value returned by a lowered `impl Trait` return type.
+        #[inline]
+        pub fn #box_fn<T>(value: T) -> #box_type<T> {
+            __alloc::boxed::Box::new(value)
+        }
+
+        /// The boxed future returned by lowered `async` methods.
+        pub type #box_future_type<T> = ::core::pin::Pin<
+            __alloc::boxed::Box<dyn ::core::future::Future<Output = T> + Send + 'static>,
+        >;
+
+        /// Box the future returned by an `async` method.
+        #[inline]
+        pub fn #box_future_fn<F>(future: F) -> #box_future_type<F::Output>
+        where
+            F: ::core::future::Future + Send + 'static,
+        {
+            __alloc::boxed::Box::pin(future)
+        }
+    }
+}
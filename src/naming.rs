@@ -2,14 +2,16 @@
 
 use quote::format_ident;
 use syn::{
-    parse_quote, punctuated::Punctuated, token::Comma, Error, Expr, FnArg, Ident, Pat, Signature,
+    parse_quote, punctuated::Punctuated, token::Comma, Error, Expr, FnArg, Ident, Pat, Path,
+    Signature,
 };
 
 /// Extract the argument list from the function signature to be used by the caller.
 ///
 /// Returns `Err(Error)` with a compile error if any argument is not an identifier.
 ///
-/// Receivers are ignored because they are already rejected by `validate_fn_signature`.
+/// Receivers are ignored because they are either rejected by `validate_fn_signature`,
+/// or not passed through the interface with the `instance` option.
 ///
 /// Returns `Ok(Punctuated<Expr, Comma>)` with the argument list.
 pub fn extract_caller_args(sig: &Signature) -> Result<Punctuated<Expr, Comma>, Error> {
@@ -17,6 +19,7 @@ pub fn extract_caller_args(sig: &Signature) -> Result<Punctuated<Expr, Comma>, E
     for arg in &sig.inputs {
         if let FnArg::Typed(t) = arg {
             if let Pat::Ident(arg_ident) = &*t.pat {
+                let arg_ident = &arg_ident.ident;
                 args.push(parse_quote! { #arg_ident });
             } else {
                 return Err(Error::new_spanned(
@@ -29,20 +32,61 @@ pub fn extract_caller_args(sig: &Signature) -> Result<Punctuated<Expr, Comma>, E
     Ok(args)
 }
 
-/// Generate a unique identifier to guard against aliasing of trait names.
-pub fn alias_guard_name(trait_name: &Ident) -> Ident {
-    format_ident!("__MustNotAnAlias__{}", trait_name)
+/// Generate the name of the associated constant holding the name of the
+/// interface, which guards against aliasing of trait names.
+///
+/// The guards are shared with `crate_interface_lite`, which can not
+/// concatenate identifiers, so their names are fixed and reserved, and the
+/// implementation checks their values. See the guard protocol in the
+/// crate-level documentation.
+pub fn alias_guard_name() -> Ident {
+    format_ident!("__InterfaceName")
 }
 
-/// Generate a unique identifier to enforce namespace matching between
+/// Generate the name of the associated constant holding the namespace of the
+/// interface (empty if none), which enforces namespace matching between
 /// `def_interface` and `impl_interface`.
-pub fn namespace_guard_name(namespace: &str) -> Ident {
-    format_ident!("__NamespaceGuard__{}", namespace)
+pub fn namespace_guard_name() -> Ident {
+    format_ident!("__InterfaceNamespace")
+}
+
+/// Generate the namespace of the symbols of an interface defined with
+/// `namespace = crate`, from the name of the defining package, like `axhal`
+/// for `axhal` or `my_driver` for `my-driver`.
+pub fn crate_namespace(package_name: &str) -> String {
+    package_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Generate the name of the associated constant that enforces
+/// `namespace = crate` to be given to both `def_interface` and
+/// `impl_interface`.
+pub fn crate_namespace_guard_name() -> Ident {
+    format_ident!("__InterfaceCrateNamespace")
+}
+
+/// Generate the name of the macro expanding to the namespace of an interface
+/// defined with `namespace = crate`, which is exported at the root of the
+/// defining crate.
+pub fn crate_namespace_macro_name(trait_name: &Ident) -> Ident {
+    format_ident!("__{}_crate_namespace", trait_name)
+}
+
+/// Generate the name under which the macro named by
+/// [`crate_namespace_macro_name`] is re-exported in the module named by
+/// [`extern_fn_mod_name`], where `impl_interface` finds it.
+pub fn namespace_macro_name() -> Ident {
+    format_ident!("__namespace")
 }
 
 /// Generate the extern function name (the symbol `def_interface` defines and
 /// `impl_interface` implements), based on the optional namespace, trait name,
 /// and function name.
+///
+/// This is the mangling scheme shared with `crate_interface_lite`:
+/// `__{Trait}_{fn}`, or `__{Namespace}_{Trait}_{fn}` with a namespace.
 pub fn extern_fn_name(namespace: Option<&str>, trait_name: &Ident, fn_name: &Ident) -> Ident {
     if let Some(ns) = namespace {
         format_ident!("__{}_{}_{}", ns, trait_name, fn_name)
@@ -58,3 +102,315 @@ pub fn extern_fn_name(namespace: Option<&str>, trait_name: &Ident, fn_name: &Ide
 pub fn extern_fn_mod_name(trait_name: &Ident) -> Ident {
     format_ident!("__{}_mod", trait_name)
 }
+
+/// Generate the path to the module that contains the extern function
+/// declarations, from the path to the trait.
+pub fn extern_fn_mod_path(trait_path: &Path) -> Path {
+    let mut path = trait_path.clone();
+    let trait_name = path.segments.pop().unwrap().into_value().ident;
+    path.segments.push(extern_fn_mod_name(&trait_name).into());
+    path
+}
+
+/// Generate the name of the module (nested in the one named by
+/// [`extern_fn_mod_name`]) that re-exports every function callable through the
+/// interface, keyed by the bare method name.
+pub fn methods_mod_name() -> Ident {
+    format_ident!("__methods")
+}
+
+/// Generate the name of the type of the hidden constant named after the trait,
+/// whose methods forward to the functions re-exported by the module named by
+/// [`methods_mod_name`]. `call_interface!` goes through the constant, so
+/// re-exporting the trait is enough to call the interface.
+pub fn resolver_type_name() -> Ident {
+    format_ident!("__Resolver")
+}
+
+/// Generate the name of the constructor of the type named by
+/// [`resolver_type_name`], which takes the resolvers of the supertraits.
+pub fn resolver_new_fn_name() -> Ident {
+    format_ident!("__crate_interface_new")
+}
+
+/// Generate the name of the method of the type named by [`resolver_type_name`]
+/// that `call_interface!` calls first. It is keyed by the trait and the
+/// namespace, so calling with a mismatched namespace fails to resolve, even
+/// through the supertrait interfaces.
+pub fn namespace_check_fn_name(namespace: Option<&str>, trait_name: &Ident) -> Ident {
+    if let Some(ns) = namespace {
+        format_ident!("__{}_ns_{}", trait_name, ns)
+    } else {
+        format_ident!("__{}_ns", trait_name)
+    }
+}
+
+/// The name of the interface that receives the tracing hooks.
+pub const TRACE_INTERFACE_NAME: &str = "InterfaceTraceIf";
+
+/// Generate the extern function name of a tracing hook (`on_enter` or
+/// `on_exit`) of the interface named by [`TRACE_INTERFACE_NAME`].
+pub fn trace_hook_fn_name(hook: &str) -> Ident {
+    extern_fn_name(
+        None,
+        &format_ident!("{}", TRACE_INTERFACE_NAME),
+        &format_ident!("{}", hook),
+    )
+}
+
+/// Generate the name of the function wrapping an extern function with the
+/// tracing hooks.
+pub fn trace_wrapper_name(fn_name: &Ident) -> Ident {
+    format_ident!("__trace_{}", fn_name)
+}
+
+/// The name of the interface that provides the clock for call statistics.
+pub const CLOCK_INTERFACE_NAME: &str = "InterfaceClockIf";
+
+/// Generate the extern function name of the clock function (`now_nanos`) of
+/// the interface named by [`CLOCK_INTERFACE_NAME`].
+pub fn clock_fn_name() -> Ident {
+    extern_fn_name(
+        None,
+        &format_ident!("{}", CLOCK_INTERFACE_NAME),
+        &format_ident!("now_nanos"),
+    )
+}
+
+/// The name of the interface that receives the panics caught at the boundary
+/// of interfaces with the `unwind = catch` policy.
+pub const PANIC_INTERFACE_NAME: &str = "InterfacePanicIf";
+
+/// Generate the extern function name of the panic hook (`on_panic`) of the
+/// interface named by [`PANIC_INTERFACE_NAME`].
+pub fn panic_hook_fn_name() -> Ident {
+    extern_fn_name(
+        None,
+        &format_ident!("{}", PANIC_INTERFACE_NAME),
+        &format_ident!("on_panic"),
+    )
+}
+
+/// Generate the name of the static holding the call statistics of an
+/// interface implementation.
+pub fn stats_registry_name(impl_name: &Ident, trait_name: &Ident) -> Ident {
+    format_ident!("__{}_{}_stats", impl_name, trait_name)
+}
+
+/// Generate the name of the static registering the call statistics of an
+/// interface implementation in [`STATS_SECTION_NAME`].
+pub fn stats_entries_name(impl_name: &Ident, trait_name: &Ident) -> Ident {
+    format_ident!("__{}_{}_stats_entries", impl_name, trait_name)
+}
+
+/// The name of the linker section collecting the call statistics of every
+/// implementation with the `stats` option, enumerated by `interface_stats!`.
+/// It is a C identifier, so the linker defines its bounds as
+/// `__start_<section>` and `__stop_<section>`.
+pub const STATS_SECTION_NAME: &str = "crate_interface_stats";
+
+/// Generate the name of the boxed future type returned by lowered `async`
+/// methods.
+pub fn box_future_type_name() -> Ident {
+    format_ident!("__BoxFuture")
+}
+
+/// Generate the name of the function that boxes the future of a lowered
+/// `async` method.
+pub fn box_future_fn_name() -> Ident {
+    format_ident!("__box_future")
+}
+
+/// Generate the name of the box type returned by lowered `impl Trait` return
+/// types.
+pub fn box_type_name() -> Ident {
+    format_ident!("__Box")
+}
+
+/// Generate the name of the function that boxes the value returned by a
+/// lowered `impl Trait` return type.
+pub fn box_fn_name() -> Ident {
+    format_ident!("__box")
+}
+
+/// Generate the name of the associated constant of a versioned interface that
+/// every implementation defines with the version it implements.
+pub fn version_guard_name() -> Ident {
+    format_ident!("__InterfaceVersion")
+}
+
+/// Generate the name of the associated constant of a versioned interface that
+/// holds its latest version.
+pub fn latest_version_name() -> Ident {
+    format_ident!("__InterfaceLatestVersion")
+}
+
+/// Generate the name of the associated constant of a versioned interface that
+/// the implementations set for each method they implement.
+pub fn implemented_guard_name(fn_name: &Ident) -> Ident {
+    format_ident!("__InterfaceImplemented_{}", fn_name.to_string())
+}
+
+/// Generate the name of the associated constant of a versioned interface that
+/// checks the implementations to implement the methods of the version they
+/// implement, which have no default implementation.
+pub fn required_methods_guard_name() -> Ident {
+    format_ident!("__InterfaceRequiredMethods")
+}
+
+/// Generate the name of the function that returns the version implemented by
+/// the implementation of a versioned interface.
+///
+/// It is exported like an interface function, so it is reserved in versioned
+/// interfaces.
+pub fn version_fn_name() -> Ident {
+    format_ident!("interface_version")
+}
+
+/// Generate the module name that contains the concrete types bound to the
+/// associated types of the interface, as type aliases named after them.
+pub fn types_mod_name() -> Ident {
+    format_ident!("__types")
+}
+
+/// Generate the name of the trait converting between the type bound to an
+/// associated type of the interface, and the one of the implementation.
+pub fn type_binding_trait_name(name: &Ident) -> Ident {
+    format_ident!("__{}Binding", name)
+}
+
+/// Generate the name of the zero-sized type that implements the interface by
+/// forwarding to the linked implementation.
+pub fn proxy_type_name(trait_name: &Ident) -> Ident {
+    format_ident!("{}Proxy", trait_name)
+}
+
+/// Generate the name of the type alias of the implementation bound to the
+/// interface with `bind!`, which `call_interface!(bound, ...)` dispatches to.
+pub fn bound_type_name(trait_name: &Ident) -> Ident {
+    format_ident!("{}Bound", trait_name)
+}
+
+/// Replace the characters of a build key other than ASCII letters and digits
+/// with `_`, so it can be part of identifiers and symbols.
+fn sanitize_select_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Generate the name of the environment variable holding the value of a build
+/// key that selects among candidate implementations, like
+/// `CRATE_INTERFACE_SELECT_BOARD` for `board`.
+pub fn select_env_var_name(key: &str) -> String {
+    format!(
+        "CRATE_INTERFACE_SELECT_{}",
+        sanitize_select_key(key).to_ascii_uppercase()
+    )
+}
+
+/// Generate the name of the associated constant that enforces the interface
+/// defined with `select = "key"` to be implemented by candidates selected by
+/// the same key.
+pub fn select_guard_name(key: &str) -> Ident {
+    format_ident!("__InterfaceSelect_{}", sanitize_select_key(key))
+}
+
+/// Generate the name of the symbol exported by the selected candidate of an
+/// interface, which the definition references so that selecting no candidate
+/// fails to link, and selecting several defines it multiple times.
+pub fn select_marker_name(key: &str, trait_name: &Ident) -> Ident {
+    format_ident!(
+        "__crate_interface_select_{}_{}",
+        sanitize_select_key(key),
+        trait_name.to_string()
+    )
+}
+
+/// Generate the name of the `#[used]` static (in the module named by
+/// [`extern_fn_mod_name`]) referencing the symbol of the selected candidate.
+pub fn select_marker_ref_name() -> Ident {
+    format_ident!("__SELECTED")
+}
+
+/// Generate the name of the associated constant holding the fingerprint of the
+/// interface, which the implementations exported with `export_dynamic` export
+/// to be checked when loading them.
+pub fn fingerprint_const_name() -> Ident {
+    format_ident!("__InterfaceFingerprint")
+}
+
+/// Generate the name of the function that returns the fingerprint of the
+/// interface, exported like an interface function by the implementations
+/// exported with `export_dynamic`.
+pub fn fingerprint_fn_name() -> Ident {
+    format_ident!("interface_fingerprint")
+}
+
+/// Generate the name of the module (nested in the one named by
+/// [`extern_fn_mod_name`]) with the functions loading shared objects.
+pub fn dylib_mod_name() -> Ident {
+    format_ident!("__dylib")
+}
+
+/// Generate the name of the type holding the functions of an implementation
+/// loaded from a shared object.
+pub fn plugin_type_name(trait_name: &Ident) -> Ident {
+    format_ident!("{}Plugin", trait_name)
+}
+
+/// Generate the name of the associated constant that enforces the unwind
+/// policy to match between `def_interface` and `impl_interface`.
+pub fn unwind_guard_name(policy: &str) -> Ident {
+    format_ident!("__InterfaceUnwind_{}", policy)
+}
+
+/// Generate the name of the function (in the module named by
+/// [`extern_fn_mod_name`]) that applies the unwind policy to a call of the
+/// implementation, with a separate one for the methods returning a `Result`.
+pub fn unwind_fn_name(returns_result: bool) -> Ident {
+    if returns_result {
+        format_ident!("__unwind_result")
+    } else {
+        format_ident!("__unwind")
+    }
+}
+
+/// Generate the name of the type of the panics caught at the boundary of an
+/// interface with the `unwind = catch` policy.
+pub fn panic_type_name(trait_name: &Ident) -> Ident {
+    format_ident!("{}Panic", trait_name)
+}
+
+/// Generate the name of the associated constant that enforces the
+/// implementations of interfaces defined with `checked` to be checked.
+pub fn checked_guard_name() -> Ident {
+    format_ident!("__InterfaceChecked")
+}
+
+/// Generate the name of the macro passing the definition of a checked
+/// interface to `impl_interface`, which is exported at the root of the
+/// defining crate. It is named like the symbols, so it is unique as well.
+pub fn definition_macro_name(namespace: Option<&str>, trait_name: &Ident) -> Ident {
+    let name = extern_fn_name(namespace, trait_name, &format_ident!("interface")).to_string();
+    format_ident!("{}", name)
+}
+
+/// Generate the name under which the macro named by [`definition_macro_name`]
+/// is re-exported in the module named by [`extern_fn_mod_name`].
+pub fn definition_macro_alias() -> Ident {
+    format_ident!("__interface")
+}
+
+/// Generate the name of the trait (in the module named by
+/// [`extern_fn_mod_name`]) holding the default implementations of a checked
+/// interface, which is implemented for every implementation of it.
+pub fn defaults_trait_name() -> Ident {
+    format_ident!("__Defaults")
+}
+
+/// Generate the name of the method of the trait named by
+/// [`defaults_trait_name`] holding the default implementation of a method.
+pub fn default_fn_name(fn_name: &Ident) -> Ident {
+    format_ident!("__default_{}", fn_name)
+}
@@ -0,0 +1,164 @@
+//! The unwind policies applied at the boundary of interfaces with the `unwind`
+//! option.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, ReturnType, Signature, Type, Visibility};
+
+use crate::args::UnwindPolicy;
+use crate::naming::{panic_hook_fn_name, panic_type_name, unwind_fn_name};
+
+/// Whether the function returns a `Result`, judged by the last segment of the
+/// path of its return type. Panics caught in such functions are returned as
+/// errors.
+pub fn returns_result(sig: &Signature) -> bool {
+    match &sig.output {
+        ReturnType::Type(_, ty) => match ty.as_ref() {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.ident == "Result"),
+            _ => false,
+        },
+        ReturnType::Default => false,
+    }
+}
+
+/// Wrap the call of an implementation (or of a default implementation) with
+/// the function applying the unwind policy, which is defined in the module
+/// at `mod_path`.
+pub fn wrap_call(
+    policy: Option<UnwindPolicy>,
+    mod_path: &TokenStream,
+    sig: &Signature,
+    call: TokenStream,
+) -> TokenStream {
+    let returns_result = match policy {
+        None | Some(UnwindPolicy::Propagate) => return call,
+        Some(UnwindPolicy::Abort) => false,
+        Some(UnwindPolicy::Catch) => returns_result(sig),
+    };
+    let unwind_fn = unwind_fn_name(returns_result);
+    let fn_str = sig.ident.to_string();
+    quote! { #mod_path::#unwind_fn(#fn_str, move || { #call }) }
+}
+
+/// Generate the functions applying the unwind policy, to be placed in the
+/// module generated by `def_interface`, and the type of the caught panics to
+/// be placed next to the trait.
+pub fn unwind_support_items(
+    policy: Option<UnwindPolicy>,
+    trait_name: &Ident,
+    vis: &Visibility,
+) -> (TokenStream, TokenStream) {
+    let trait_str = trait_name.to_string();
+    let unwind_fn = unwind_fn_name(false);
+    match policy {
+        None | Some(UnwindPolicy::Propagate) => (quote! {}, quote! {}),
+        Some(UnwindPolicy::Abort) => {
+            let message = format!("aborting on a panic in `{trait_str}`");
+            // Panicking again while unwinding aborts the process, which also
+            // works without `std`.
+            let support = quote! {
+                #[allow(dead_code)]
+                #[inline]
+                pub fn #unwind_fn<R>(_method: &'static str, f: impl FnOnce() -> R) -> R {
+                    struct AbortOnUnwind;
+
+                    impl Drop for AbortOnUnwind {
+                        fn drop(&mut self) {
+                            ::core::panic!(#message);
+                        }
+                    }
+
+                    let guard = AbortOnUnwind;
+                    let ret = f();
+                    ::core::mem::forget(guard);
+                    ret
+                }
+            };
+            (support, quote! {})
+        }
+        Some(UnwindPolicy::Catch) => {
+            let panic_name = panic_type_name(trait_name);
+            let panic_hook = panic_hook_fn_name();
+            let unwind_result_fn = unwind_fn_name(true);
+            let doc = format!(
+                " A panic caught at the boundary of [`{trait_str}`], returned by its methods returning a `Result`."
+            );
+            let panic_type = quote! {
+                #[doc = #doc]
+                #[allow(dead_code)]
+                #[derive(Clone, Debug, PartialEq, Eq)]
+                #vis struct #panic_name {
+                    /// The name of the interface.
+                    pub trait_name: &'static str,
+                    /// The name of the method that panicked.
+                    pub method: &'static str,
+                    /// The message of the panic, if it is a string.
+                    pub message: ::std::string::String,
+                }
+
+                impl ::core::fmt::Display for #panic_name {
+                    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                        ::core::write!(f, "`{}::{}` panicked: {}", self.trait_name, self.method, self.message)
+                    }
+                }
+
+                impl ::std::error::Error for #panic_name {}
+            };
+            let support = quote! {
+                extern "Rust" {
+                    fn #panic_hook(trait_name: &'static str, method: &'static str, message: &str) -> !;
+                }
+
+                fn __catch<R>(
+                    method: &'static str,
+                    f: impl FnOnce() -> R,
+                ) -> ::core::result::Result<R, #panic_name> {
+                    ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+                        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+                            ::std::string::ToString::to_string(message)
+                        } else if let Some(message) = payload.downcast_ref::<::std::string::String>() {
+                            ::core::clone::Clone::clone(message)
+                        } else {
+                            ::std::string::String::from("Box<dyn Any>")
+                        };
+                        #panic_name {
+                            trait_name: #trait_str,
+                            method,
+                            message,
+                        }
+                    })
+                }
+
+                #[allow(dead_code)]
+                #[inline]
+                pub fn #unwind_fn<R>(method: &'static str, f: impl FnOnce() -> R) -> R {
+                    match __catch(method, f) {
+                        ::core::result::Result::Ok(ret) => ret,
+                        ::core::result::Result::Err(panic) => unsafe {
+                            #panic_hook(panic.trait_name, panic.method, &panic.message)
+                        },
+                    }
+                }
+
+                #[allow(dead_code)]
+                #[inline]
+                pub fn #unwind_result_fn<T, E: ::core::convert::From<#panic_name>>(
+                    method: &'static str,
+                    f: impl FnOnce() -> ::core::result::Result<T, E>,
+                ) -> ::core::result::Result<T, E> {
+                    match __catch(method, f) {
+                        ::core::result::Result::Ok(ret) => ret,
+                        ::core::result::Result::Err(panic) => {
+                            ::core::result::Result::Err(::core::convert::From::from(panic))
+                        }
+                    }
+                }
+            };
+            (support, panic_type)
+        }
+    }
+}
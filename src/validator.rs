@@ -1,26 +1,76 @@
 //! Validator utilities for the crate interface.
 
-use syn::{Error, FnArg, Signature};
+use syn::{visit::Visit, Error, FnArg, GenericParam, ReturnType, Signature, TypeImplTrait};
 
-use crate::errors::generic_not_allowed_error;
+use crate::errors::{generic_not_allowed_error, impl_trait_not_allowed_error};
 
-/// Validate the function signature, rejecting generic parameters and receivers.
+/// Find the first `impl Trait` type in the signature.
+fn find_impl_trait(sig: &Signature) -> Option<&TypeImplTrait> {
+    /// Visitor that records the first `impl Trait` type.
+    struct ImplTraitFinder<'a> {
+        found: Option<&'a TypeImplTrait>,
+    }
+
+    impl<'a> Visit<'a> for ImplTraitFinder<'a> {
+        fn visit_type_impl_trait(&mut self, ty: &'a TypeImplTrait) {
+            self.found.get_or_insert(ty);
+        }
+    }
+
+    let mut finder = ImplTraitFinder { found: None };
+    for arg in &sig.inputs {
+        if let FnArg::Typed(arg) = arg {
+            finder.visit_type(&arg.ty);
+        }
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        finder.visit_type(ty);
+    }
+    finder.found
+}
+
+/// Validate the function signature, rejecting generic type and const
+/// parameters, receivers and `impl Trait` types. With `instance`, `&self`
+/// receivers are allowed, which are forwarded to the registered instance.
 ///
 /// Returns `Err(Error)` with a compile error if:
-/// - The function has generic parameters
-/// - Any argument is a receiver (`self`, `&self`, `&mut self`)
-pub fn validate_fn_signature(sig: &Signature) -> Result<(), Error> {
-    if !sig.generics.params.is_empty() {
-        return Err(generic_not_allowed_error(&sig.generics));
+/// - The function has generic type or const parameters
+/// - Any argument is a receiver (`self`, `&self`, `&mut self`), other than
+///   `&self` with `instance`
+/// - Any argument or the return type contains `impl Trait`
+pub fn validate_fn_signature(sig: &Signature, instance: bool) -> Result<(), Error> {
+    // Lifetime parameters are erased in the symbol, so they are allowed.
+    if let Some(param) = sig
+        .generics
+        .params
+        .iter()
+        .find(|param| !matches!(param, GenericParam::Lifetime(_)))
+    {
+        return Err(generic_not_allowed_error(param));
     }
 
     for arg in &sig.inputs {
         if let FnArg::Receiver(receiver) = arg {
-            return Err(Error::new_spanned(
-                receiver,
-                "methods with receiver (self) are not allowed in crate_interface",
-            ));
+            if !instance {
+                return Err(Error::new_spanned(
+                    receiver,
+                    "methods with receiver (self) are not allowed in crate_interface without the `instance` option",
+                ));
+            }
+            if receiver.reference.is_none()
+                || receiver.mutability.is_some()
+                || receiver.colon_token.is_some()
+            {
+                return Err(Error::new_spanned(
+                    receiver,
+                    "only `&self` receivers are allowed, since the instance is a `static`",
+                ));
+            }
         }
     }
+
+    if let Some(impl_trait) = find_impl_trait(sig) {
+        return Err(impl_trait_not_allowed_error(impl_trait));
+    }
     Ok(())
 }
@@ -0,0 +1,23 @@
+//! Define traits whose implementations are loaded from shared objects.
+//!
+//! This crate enables the `dylib` feature of `crate_interface`, so `DriverIf`
+//! can be defined with the `dylib` option, which generates a `DriverIfPlugin`
+//! type loading its implementation.
+
+use crate_interface::def_interface;
+
+/// The interface of an optional driver shipped as a plugin.
+#[def_interface(namespace = PluginNs, dylib)]
+pub trait DriverIf {
+    /// Get the name of the driver.
+    fn driver_name() -> &'static str;
+
+    /// Probe a device, returning the number of its queues.
+    fn probe(device_id: u32) -> Option<usize>;
+
+    /// Write to the device, returning the number of bytes written.
+    fn write(buf: &[u8]) -> usize;
+
+    /// Get the first word of a line.
+    fn first_word<'a>(line: &'a str) -> &'a str;
+}
@@ -41,3 +41,47 @@ pub trait AdvancedIf {
     /// Complex computation.
     fn process(input: u64) -> u64;
 }
+
+/// A base interface to be inherited.
+#[def_interface]
+pub trait DeviceIf {
+    /// Get the device name.
+    fn device_name() -> &'static str;
+}
+
+/// An interface inheriting `DeviceIf`.
+#[def_interface]
+pub trait NetDeviceIf: DeviceIf {
+    /// Get the maximum transmission unit.
+    fn mtu() -> usize;
+}
+
+/// An interface namespaced with the name of this package, so it does not
+/// collide with the `LogIf` of `impl-simple-traits`.
+#[def_interface(namespace = crate)]
+pub trait LogIf {
+    /// Get the target of the log records.
+    fn log_target() -> &'static str;
+}
+
+fn default_timer_frequency() -> u64 {
+    1_000_000
+}
+
+/// A checked interface, whose implementations are checked against its
+/// definition and get the default implementations without weak symbols.
+#[def_interface(checked)]
+pub trait TimerIf {
+    /// Get the current tick count.
+    fn ticks() -> u64;
+
+    /// Get the frequency of the ticks, in Hz.
+    fn frequency() -> u64 {
+        default_timer_frequency()
+    }
+
+    /// Get the time in microseconds.
+    fn micros() -> u64 {
+        Self::ticks() * 1_000_000 / Self::frequency()
+    }
+}
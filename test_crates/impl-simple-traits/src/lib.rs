@@ -3,8 +3,16 @@
 //! This crate demonstrates that trait definition and implementation can be in
 //! separate crates, which is a key feature of `crate_interface`.
 
-use crate_interface::impl_interface;
-use define_simple_traits::{AdvancedIf, CallerIf, NamespacedIf, SimpleIf};
+use crate_interface::{def_interface, impl_interface};
+use define_simple_traits::{AdvancedIf, CallerIf, DeviceIf, NamespacedIf, NetDeviceIf, SimpleIf};
+
+/// Another interface named `LogIf`, implemented in `test-simple`. Both are
+/// namespaced with the name of their package, so their symbols differ.
+#[def_interface(namespace = crate)]
+pub trait LogIf {
+    /// Get the target of the log records.
+    fn log_target() -> &'static str;
+}
 
 /// Implementation struct for SimpleIf.
 pub struct SimpleImpl;
@@ -57,3 +65,40 @@ impl AdvancedIf for AdvancedImpl {
         input * 2 + 100
     }
 }
+
+/// Implementation struct for NetDeviceIf and its supertrait DeviceIf.
+pub struct NetDeviceImpl;
+
+#[impl_interface]
+impl DeviceIf for NetDeviceImpl {
+    fn device_name() -> &'static str {
+        "eth0"
+    }
+}
+
+#[impl_interface]
+impl NetDeviceIf for NetDeviceImpl {
+    fn mtu() -> usize {
+        1500
+    }
+}
+
+/// Implementation struct for the `LogIf` of `define-simple-traits`.
+pub struct LogImpl;
+
+#[impl_interface(namespace = crate)]
+impl define_simple_traits::LogIf for LogImpl {
+    fn log_target() -> &'static str {
+        "define-simple-traits"
+    }
+}
+
+/// Implementation struct for TimerIf.
+pub struct TimerImpl;
+
+#[impl_interface(checked)]
+impl define_simple_traits::TimerIf for TimerImpl {
+    fn ticks() -> u64 {
+        3_000
+    }
+}
@@ -0,0 +1,25 @@
+//! Implement `DriverIf` in a shared object, loaded by `test-plugin`.
+
+use crate_interface::impl_interface;
+use define_plugin_traits::DriverIf;
+
+pub struct VirtioDriver;
+
+#[impl_interface(namespace = PluginNs, export_dynamic)]
+impl DriverIf for VirtioDriver {
+    fn driver_name() -> &'static str {
+        "virtio"
+    }
+
+    fn probe(device_id: u32) -> Option<usize> {
+        (device_id == 0x1af4).then_some(4)
+    }
+
+    fn write(buf: &[u8]) -> usize {
+        buf.len()
+    }
+
+    fn first_word<'a>(line: &'a str) -> &'a str {
+        line.split_whitespace().next().unwrap_or_default()
+    }
+}
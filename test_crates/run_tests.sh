@@ -8,6 +8,8 @@
 #   ./run_tests.sh           # Run all tests
 #   ./run_tests.sh simple    # Run only simple tests (stable Rust)
 #   ./run_tests.sh weak      # Run only weak_default tests (nightly Rust)
+#   ./run_tests.sh plugin    # Run only plugin loading tests (dylib feature)
+#   ./run_tests.sh symbols   # Check only the placement of exported symbols (ELF)
 
 set -e
 
@@ -61,10 +63,86 @@ run_weak_tests() {
     echo_status "Weak_default trait tests passed!"
 }
 
+run_plugin_tests() {
+    echo_status "Running plugin loading tests (dylib feature)..."
+
+    echo_status "  Building plugin-driver..."
+    cargo build -p plugin-driver
+
+    echo_status "  Building and running test-plugin..."
+    cargo run --bin test-plugin
+
+    echo_status "Plugin loading tests passed!"
+}
+
+# Check that `symbol` is defined in the input section `section` of the object
+# file `obj`, with the `visibility` shown by objdump (`.hidden`, `.protected`).
+check_symbol() {
+    local obj="$1" symbol="$2" section="$3" visibility="$4"
+    local pattern="[[:space:]]${section//./\\.}[[:space:]]+[0-9a-f]+ ${visibility//./\\.} ${symbol}\$"
+
+    if ! objdump -t "$obj" | grep -qE "$pattern"; then
+        echo_error "$symbol is not $visibility in $section:"
+        objdump -t "$obj" | grep -F "$symbol" || true
+        exit 1
+    fi
+}
+
+# Check that `symbol` is defined in the object file `obj` with the default
+# visibility, which objdump does not show.
+check_default_visibility() {
+    local obj="$1" symbol="$2"
+
+    if ! objdump -t "$obj" | grep -qE "[[:space:]][0-9a-f]+ ${symbol}\$"; then
+        echo_error "$symbol does not have the default visibility:"
+        objdump -t "$obj" | grep -F "$symbol" || true
+        exit 1
+    fi
+}
+
+run_symbol_tests() {
+    echo_status "Running symbol placement tests (ELF only)..."
+
+    # The linker merges the sections, so the object files are checked.
+    local root="$SCRIPT_DIR/.."
+    local out="$root/target/symbols"
+    mkdir -p "$out"
+
+    echo_status "  Checking the exported functions of test_crate_interface..."
+    (cd "$root" && CARGO_INCREMENTAL=0 cargo rustc --test test_crate_interface -- \
+        -C codegen-units=1 --emit=obj="$out/test_crate_interface.o")
+    check_symbol "$out/test_crate_interface.o" __BootIf_boot_stage .text.boot .hidden
+    check_symbol "$out/test_crate_interface.o" __BootIf_boot_console .text.boot.console .protected
+
+    echo_status "  Checking the selected candidate of test_select..."
+    (cd "$root" && CARGO_INCREMENTAL=0 cargo rustc --test test_select -- \
+        -C codegen-units=1 --emit=obj="$out/test_select.o")
+    check_default_visibility "$out/test_select.o" __BoardIf_board_name
+    check_default_visibility "$out/test_select.o" __BoardIf_uart_base
+
+    if ! rustc --version | grep -q nightly; then
+        CARGO_CMD="cargo +nightly"
+    else
+        CARGO_CMD="cargo"
+    fi
+
+    echo_status "  Checking the weak default implementations of test_weak_default..."
+    (cd "$root" && CARGO_INCREMENTAL=0 $CARGO_CMD rustc --features weak_default --test test_weak_default -- \
+        -C codegen-units=1 --emit=obj="$out/test_weak_default.o")
+    check_symbol "$out/test_weak_default.o" __EarlyIf_early_stage .text.boot .hidden
+    check_symbol "$out/test_weak_default.o" __EarlyIf_early_name .text.boot.name .protected
+
+    echo_status "Symbol placement tests passed!"
+}
+
 run_all_tests() {
     run_simple_tests
     echo ""
     run_weak_tests
+    echo ""
+    run_plugin_tests
+    echo ""
+    run_symbol_tests
 }
 
 # Parse arguments
@@ -75,13 +153,21 @@ case "${1:-all}" in
     weak)
         run_weak_tests
         ;;
+    plugin)
+        run_plugin_tests
+        ;;
+    symbols)
+        run_symbol_tests
+        ;;
     all)
         run_all_tests
         ;;
     *)
-        echo "Usage: $0 [simple|weak|all]"
+        echo "Usage: $0 [simple|weak|plugin|symbols|all]"
         echo "  simple  - Run only simple tests (stable Rust)"
         echo "  weak    - Run only weak_default tests (nightly Rust)"
+        echo "  plugin  - Run only plugin loading tests (dylib feature)"
+        echo "  symbols - Check only the placement of exported symbols (ELF)"
         echo "  all     - Run all tests (default)"
         exit 1
         ;;
@@ -0,0 +1,34 @@
+//! Test binary loading the implementation of `DriverIf` from the shared object
+//! built by `plugin-driver`, which must be built first.
+
+use define_plugin_traits::DriverIfPlugin;
+
+fn main() {
+    println!("=== Testing plugin loading ===\n");
+
+    let exe = std::env::current_exe().unwrap();
+    let path = exe.with_file_name(format!(
+        "{}plugin_driver{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    ));
+    let plugin = unsafe { DriverIfPlugin::dlopen(path.to_str().unwrap()) }.unwrap();
+    println!("  [OK] Loaded {}", path.display());
+
+    assert_eq!(plugin.driver_name(), "virtio");
+    println!("  [OK] DriverIf::driver_name() = {}", plugin.driver_name());
+
+    assert_eq!(plugin.probe(0x1af4), Some(4));
+    assert_eq!(plugin.probe(0x8086), None);
+    println!("  [OK] DriverIf::probe()");
+
+    assert_eq!(plugin.write(b"hello"), 5);
+    assert_eq!(plugin.first_word("  virtio-net ready"), "virtio-net");
+    println!("  [OK] DriverIf::write() and DriverIf::first_word()");
+
+    let missing = unsafe { DriverIfPlugin::dlopen("libmissing_driver.so") };
+    assert!(missing.is_err());
+    println!("  [OK] Loading a missing shared object fails");
+
+    println!("\n=== All plugin tests passed! ===");
+}
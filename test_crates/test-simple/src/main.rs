@@ -5,13 +5,17 @@
 //! 2. call_interface! macro works correctly across crates
 //! 3. Namespaced traits work correctly
 //! 4. gen_caller helper functions work correctly
+//! 5. Supertrait methods can be called through the subtrait
+//! 6. Interfaces of the same name are namespaced with their defining crate
 //!
 //! Exit code 0 means all tests passed.
 
-use crate_interface::call_interface;
+use crate_interface::{call_interface, impl_interface};
 
 // Import the implementation crate to link the implementations
-use impl_simple_traits::{AdvancedImpl, CallerImpl, NamespacedImpl, SimpleImpl};
+use impl_simple_traits::{
+    AdvancedImpl, CallerImpl, LogImpl, NamespacedImpl, NetDeviceImpl, SimpleImpl,
+};
 
 // Suppress unused warnings - these are used for linking
 const _: () = {
@@ -19,8 +23,20 @@ const _: () = {
     let _ = std::any::type_name::<NamespacedImpl>;
     let _ = std::any::type_name::<CallerImpl>;
     let _ = std::any::type_name::<AdvancedImpl>;
+    let _ = std::any::type_name::<NetDeviceImpl>;
+    let _ = std::any::type_name::<LogImpl>;
 };
 
+/// Implementation struct for the `LogIf` of `impl-simple-traits`.
+struct LocalLogImpl;
+
+#[impl_interface(namespace = crate)]
+impl impl_simple_traits::LogIf for LocalLogImpl {
+    fn log_target() -> &'static str {
+        "impl-simple-traits"
+    }
+}
+
 fn test_simple_interface() {
     assert_eq!(
         call_interface!(define_simple_traits::SimpleIf::get_value),
@@ -39,7 +55,10 @@ fn test_simple_interface() {
 
 fn test_namespaced_interface() {
     assert_eq!(
-        call_interface!(namespace = SimpleNs, define_simple_traits::NamespacedIf::get_id),
+        call_interface!(
+            namespace = SimpleNs,
+            define_simple_traits::NamespacedIf::get_id
+        ),
         999
     );
     println!("  [PASS] test_namespaced_interface");
@@ -64,7 +83,11 @@ fn test_caller_interface() {
 
 fn test_advanced_interface() {
     assert_eq!(
-        call_interface!(namespace = AdvancedNs, define_simple_traits::AdvancedIf::process, 50),
+        call_interface!(
+            namespace = AdvancedNs,
+            define_simple_traits::AdvancedIf::process,
+            50
+        ),
         200 // 50 * 2 + 100 = 200
     );
 
@@ -81,6 +104,47 @@ fn test_multiple_calls() {
     println!("  [PASS] test_multiple_calls");
 }
 
+fn test_supertrait_interface() {
+    assert_eq!(
+        call_interface!(define_simple_traits::NetDeviceIf::mtu),
+        1500
+    );
+    assert_eq!(
+        call_interface!(define_simple_traits::NetDeviceIf::device_name),
+        "eth0"
+    );
+    assert_eq!(
+        call_interface!(define_simple_traits::DeviceIf::device_name),
+        "eth0"
+    );
+    println!("  [PASS] test_supertrait_interface");
+}
+
+fn test_crate_namespaced_interface() {
+    assert_eq!(
+        call_interface!(define_simple_traits::LogIf::log_target),
+        "define-simple-traits"
+    );
+    assert_eq!(
+        call_interface!(impl_simple_traits::LogIf::log_target),
+        "impl-simple-traits"
+    );
+    println!("  [PASS] test_crate_namespaced_interface");
+}
+
+fn test_checked_interface() {
+    assert_eq!(call_interface!(define_simple_traits::TimerIf::ticks), 3_000);
+    assert_eq!(
+        call_interface!(define_simple_traits::TimerIf::frequency),
+        1_000_000
+    );
+    assert_eq!(
+        call_interface!(define_simple_traits::TimerIf::micros),
+        3_000
+    );
+    println!("  [PASS] test_checked_interface");
+}
+
 fn main() {
     println!("Running simple trait tests...");
 
@@ -89,6 +153,9 @@ fn main() {
     test_caller_interface();
     test_advanced_interface();
     test_multiple_calls();
+    test_supertrait_interface();
+    test_crate_namespaced_interface();
+    test_checked_interface();
 
     println!("All simple trait tests passed!");
 }
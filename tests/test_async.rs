@@ -0,0 +1,64 @@
+#![cfg(feature = "alloc")]
+
+//! Test async interface methods.
+//!
+//! This test requires the `alloc` feature to be enabled.
+//! Run with: cargo test --features alloc --test test_async
+
+use std::future::Future;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+use crate_interface::*;
+
+/// Poll a future to completion on the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[def_interface(gen_caller)]
+trait NetDriverIf {
+    async fn recv(len: usize) -> Vec<u8>;
+    async fn flush();
+    fn mtu() -> usize;
+}
+
+struct NetDriverImpl;
+
+#[impl_interface]
+impl NetDriverIf for NetDriverImpl {
+    async fn recv(len: usize) -> Vec<u8> {
+        flush().await;
+        vec![0xab; len]
+    }
+
+    async fn flush() {}
+
+    fn mtu() -> usize {
+        1500
+    }
+}
+
+#[test]
+fn test_async_methods() {
+    assert_eq!(
+        block_on(call_interface!(NetDriverIf::recv(3))),
+        [0xab, 0xab, 0xab]
+    );
+    assert_eq!(block_on(recv(1)), [0xab]);
+    block_on(call_interface!(NetDriverIf::flush));
+    assert_eq!(mtu(), 1500);
+}
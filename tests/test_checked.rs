@@ -0,0 +1,100 @@
+//! Test implementations checked against the definition of `checked`
+//! interfaces.
+
+use crate_interface::*;
+
+fn default_irq_count() -> usize {
+    16
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PlatformError;
+
+/// A checked interface, whose default implementations are copied into the
+/// implementations omitting them, so they need no weak symbols.
+#[def_interface(checked, namespace = PlatformNs, types(Error = PlatformError))]
+trait PlatformIf {
+    type Error;
+
+    fn cpu_count() -> usize;
+
+    fn irq_count() -> usize {
+        // Resolved where the interface is defined.
+        default_irq_count()
+    }
+
+    fn total_lines() -> usize {
+        Self::cpu_count() * Self::irq_count()
+    }
+
+    fn shutdown() -> Result<(), Self::Error> {
+        Err(PlatformError)
+    }
+
+    unsafe fn read_reg(addr: *const u32) -> u32 {
+        *addr
+    }
+}
+
+struct PlatformImpl;
+
+#[impl_interface(checked, namespace = PlatformNs)]
+impl PlatformIf for PlatformImpl {
+    type Error = PlatformError;
+
+    fn cpu_count() -> usize {
+        4
+    }
+
+    fn irq_count() -> usize {
+        32
+    }
+}
+
+/// A checked versioned interface with a retired method.
+#[def_interface(checked, version = 2)]
+trait ConsoleIf {
+    #[interface(replaced_by = write_bytes)]
+    fn putchars(bytes: &[u8]) -> usize;
+
+    #[since(2)]
+    fn write_bytes(bytes: &[u8]) -> usize;
+}
+
+struct ConsoleImpl;
+
+#[impl_interface(checked, version = 2)]
+impl ConsoleIf for ConsoleImpl {
+    fn write_bytes(bytes: &[u8]) -> usize {
+        bytes.len()
+    }
+}
+
+#[test]
+fn test_checked_defaults() {
+    assert_eq!(
+        call_interface!(namespace = PlatformNs, PlatformIf::cpu_count()),
+        4
+    );
+    assert_eq!(
+        call_interface!(namespace = PlatformNs, PlatformIf::total_lines()),
+        128
+    );
+    assert_eq!(
+        call_interface!(namespace = PlatformNs, PlatformIf::shutdown()),
+        Err(PlatformError)
+    );
+    let reg = 0x1234;
+    assert_eq!(
+        unsafe { call_interface!(namespace = PlatformNs, PlatformIf::read_reg(&reg)) },
+        0x1234
+    );
+    assert_eq!(<PlatformImpl as PlatformIf>::irq_count(), 32);
+}
+
+#[test]
+fn test_checked_replaced_method() {
+    assert_eq!(call_interface!(ConsoleIf::putchars(b"hello")), 5);
+    assert_eq!(call_interface!(ConsoleIf::write_bytes(b"hi")), 2);
+    assert_eq!(call_interface!(ConsoleIf::interface_version()), 2);
+}
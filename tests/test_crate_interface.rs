@@ -1,4 +1,5 @@
 use crate_interface::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[def_interface]
 trait SimpleIf {
@@ -69,6 +70,343 @@ impl b::NamespaceIf for NamespaceIfImplB {
     }
 }
 
+mod device {
+    #[crate_interface::def_interface]
+    pub trait DeviceIf {
+        fn device_id() -> u32;
+    }
+}
+
+#[def_interface(gen_caller)]
+trait NetIf: device::DeviceIf {
+    fn mtu() -> usize;
+}
+
+struct NetIfImpl;
+
+#[impl_interface]
+impl device::DeviceIf for NetIfImpl {
+    fn device_id() -> u32 {
+        7
+    }
+}
+
+#[impl_interface]
+impl NetIf for NetIfImpl {
+    fn mtu() -> usize {
+        1500
+    }
+}
+
+#[def_interface]
+trait WifiIf: self::device::DeviceIf {
+    fn ssid() -> &'static str;
+}
+
+#[impl_interface]
+impl WifiIf for NetIfImpl {
+    fn ssid() -> &'static str {
+        "arceos"
+    }
+}
+
+mod wlan {
+    #[crate_interface::def_interface]
+    pub trait WlanIf: super::WifiIf {
+        fn channel() -> u8;
+    }
+}
+
+#[impl_interface]
+impl wlan::WlanIf for NetIfImpl {
+    fn channel() -> u8 {
+        6
+    }
+}
+
+/// An interface whose method names are also the ones of the generated items,
+/// called through a subtrait.
+#[def_interface]
+trait ProcessIf {
+    fn clone(pid: u32) -> u32;
+    fn __new() -> u32;
+}
+
+#[def_interface]
+trait ThreadIf: ProcessIf {
+    fn spawn() -> u32;
+}
+
+struct ProcessIfImpl;
+
+#[impl_interface]
+impl ProcessIf for ProcessIfImpl {
+    fn clone(pid: u32) -> u32 {
+        pid + 1
+    }
+
+    fn __new() -> u32 {
+        1
+    }
+}
+
+#[impl_interface]
+impl ThreadIf for ProcessIfImpl {
+    fn spawn() -> u32 {
+        2
+    }
+}
+
+#[def_interface(gen_caller)]
+trait SignatureIf {
+    unsafe fn read_byte(ptr: *const u8) -> u8;
+    extern "C" fn c_add(a: u32, b: u32) -> u32;
+    fn longer<'a>(a: &'a str, b: &'a str) -> &'a str;
+    fn count_down(n: u32, #[allow(unused_variables)] unused: u32) -> u32;
+}
+
+struct SignatureIfImpl;
+
+#[impl_interface]
+impl SignatureIf for SignatureIfImpl {
+    unsafe fn read_byte(ptr: *const u8) -> u8 {
+        *ptr
+    }
+
+    extern "C" fn c_add(a: u32, b: u32) -> u32 {
+        a + b
+    }
+
+    fn longer<'a>(a: &'a str, b: &'a str) -> &'a str {
+        if a.len() >= b.len() {
+            a
+        } else {
+            b
+        }
+    }
+
+    fn count_down(mut n: u32, #[allow(unused_variables)] unused: u32) -> u32 {
+        let mut steps = 0;
+        while n > 0 {
+            n -= 1;
+            steps += 1;
+        }
+        steps
+    }
+}
+
+#[def_interface(gen_caller)]
+trait CfgIf {
+    #[cfg(target_pointer_width = "64")]
+    fn word_bits() -> u64;
+    #[cfg(not(target_pointer_width = "64"))]
+    fn word_bits() -> u32;
+    #[cfg(any())]
+    fn never();
+}
+
+struct CfgIfImpl;
+
+#[impl_interface]
+impl CfgIf for CfgIfImpl {
+    #[cfg(target_pointer_width = "64")]
+    fn word_bits() -> u64 {
+        64
+    }
+    #[cfg(not(target_pointer_width = "64"))]
+    fn word_bits() -> u32 {
+        usize::BITS
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BusError(u32);
+
+#[def_interface(gen_caller, types(Error = BusError, Word = u16))]
+trait BusIf {
+    type Error;
+    type Word: Copy;
+    fn read_word(addr: usize) -> Result<Self::Word, Self::Error>;
+    fn write_word(addr: usize, word: <Self as BusIf>::Word) -> Result<(), Self::Error>;
+}
+
+struct BusIfImpl;
+
+#[impl_interface]
+impl BusIf for BusIfImpl {
+    type Error = BusError;
+    type Word = u16;
+
+    fn read_word(addr: usize) -> Result<u16, BusError> {
+        if addr % 2 == 0 {
+            Ok(addr as u16)
+        } else {
+            Err(BusError(addr as u32))
+        }
+    }
+
+    fn write_word(addr: usize, word: Self::Word) -> Result<(), Self::Error> {
+        Self::read_word(addr).map(|_| assert_eq!(word, 0xbeef))
+    }
+}
+
+#[def_interface(gen_caller)]
+trait TaskIf {
+    type Task: Sized = opaque!(size = 32, align = 8);
+    fn task_spawn(id: u64) -> Self::Task;
+    fn task_id(task: &Self::Task) -> u64;
+    fn task_rename(task: &mut Self::Task, name: &'static str);
+    fn task_join(task: Self::Task) -> &'static str;
+}
+
+struct TaskIfImpl;
+
+struct RealTask {
+    id: u64,
+    name: &'static str,
+}
+
+#[impl_interface]
+impl TaskIf for TaskIfImpl {
+    type Task = RealTask;
+
+    fn task_spawn(id: u64) -> Self::Task {
+        RealTask { id, name: "idle" }
+    }
+
+    fn task_id(task: &Self::Task) -> u64 {
+        task.id
+    }
+
+    fn task_rename(task: &mut Self::Task, name: &'static str) {
+        task.name = name;
+    }
+
+    fn task_join(task: Self::Task) -> &'static str {
+        task.name
+    }
+}
+
+/// An opaque type bound to a primitive, which also appears as plain arguments
+/// and return values that must not be converted.
+#[def_interface]
+trait TicketIf {
+    type Ticket: Sized = opaque!(size = 16, align = 8);
+    fn ticket_issue(seq: u64, offset: u64) -> Self::Ticket;
+    fn ticket_seq(ticket: &Self::Ticket) -> u64;
+    fn ticket_advance(ticket: &mut Self::Ticket, step: u64) -> u64;
+}
+
+struct TicketIfImpl;
+
+#[impl_interface]
+impl TicketIf for TicketIfImpl {
+    type Ticket = u64;
+
+    fn ticket_issue(seq: u64, offset: u64) -> Self::Ticket {
+        seq + offset
+    }
+
+    fn ticket_seq(ticket: &Self::Ticket) -> u64 {
+        *ticket
+    }
+
+    fn ticket_advance(ticket: &mut Self::Ticket, step: u64) -> u64 {
+        *ticket += step;
+        *ticket
+    }
+}
+
+#[def_interface(gen_caller, instance)]
+trait ConsoleIf {
+    fn console_write(&self, data: &[u8]) -> usize;
+    fn console_name(&self) -> &str;
+    fn console_count() -> usize;
+}
+
+struct Console {
+    name: &'static str,
+    written: AtomicUsize,
+}
+
+static CONSOLE: Console = Console {
+    name: "ttyS0",
+    written: AtomicUsize::new(0),
+};
+
+#[impl_interface(instance = CONSOLE)]
+impl ConsoleIf for Console {
+    fn console_write(&self, data: &[u8]) -> usize {
+        self.written.fetch_add(data.len(), Ordering::Relaxed);
+        data.len()
+    }
+
+    fn console_name(&self) -> &str {
+        self.name
+    }
+
+    fn console_count() -> usize {
+        CONSOLE.written.load(Ordering::Relaxed)
+    }
+}
+
+mod facade {
+    mod storage {
+        #[crate_interface::def_interface(namespace = StorageNs)]
+        pub trait StorageIf {
+            fn capacity() -> usize;
+        }
+
+        #[crate_interface::def_interface]
+        pub trait BlockIf: StorageIf {
+            fn block_size() -> usize;
+        }
+    }
+
+    pub use self::storage::{BlockIf, StorageIf};
+}
+
+struct StorageIfImpl;
+
+#[impl_interface(namespace = StorageNs)]
+impl facade::StorageIf for StorageIfImpl {
+    fn capacity() -> usize {
+        1 << 20
+    }
+}
+
+#[impl_interface]
+impl facade::BlockIf for StorageIfImpl {
+    fn block_size() -> usize {
+        512
+    }
+}
+
+#[def_interface]
+trait BootIf {
+    fn boot_stage() -> u32;
+    fn boot_console() -> &'static str;
+}
+
+struct BootIfImpl;
+
+#[impl_interface(link_section = ".text.boot", visibility = hidden)]
+impl BootIf for BootIfImpl {
+    fn boot_stage() -> u32 {
+        1
+    }
+
+    #[interface(link_section = ".text.boot.console", visibility = protected)]
+    fn boot_console() -> &'static str {
+        "ttyS0"
+    }
+}
+
+bind!(WithCallerIf => WithCallerIfImpl);
+bind!(NetIf => NetIfImpl);
+bind!(device::DeviceIf => NetIfImpl);
+bind!(a::NamespaceIf => NamespaceIfImplA);
+
 mod private {
     pub fn test_call_in_mod() {
         crate::call_interface!(super::SimpleIf::bar(123, &[2, 3, 5, 7, 11], "test"));
@@ -96,3 +434,130 @@ fn test_namespace_interface() {
     assert_eq!(a::qux(), 1);
     assert_eq!(b::qux(), 2);
 }
+
+#[test]
+fn test_supertrait_interface() {
+    assert_eq!(call_interface!(NetIf::mtu), 1500);
+    assert_eq!(call_interface!(NetIf::device_id()), 7);
+    assert_eq!(call_interface!(device::DeviceIf::device_id()), 7);
+    assert_eq!(mtu(), 1500);
+    assert_eq!(call_interface!(WifiIf::device_id()), 7);
+    assert_eq!(call_interface!(wlan::WlanIf::ssid()), "arceos");
+    assert_eq!(call_interface!(wlan::WlanIf::channel()), 6);
+    assert_eq!(call_interface!(ProcessIf::__new()), 1);
+    assert_eq!(call_interface!(ThreadIf::__new()), 1);
+    assert_eq!(call_interface!(ThreadIf::clone(3)), 4);
+    assert_eq!(call_interface!(ThreadIf::spawn()), 2);
+}
+
+#[test]
+fn test_facade_reexport() {
+    assert_eq!(
+        call_interface!(namespace = StorageNs, facade::StorageIf::capacity()),
+        1 << 20
+    );
+    assert_eq!(call_interface!(facade::BlockIf::block_size()), 512);
+    assert_eq!(call_interface!(facade::BlockIf::capacity()), 1 << 20);
+}
+
+#[test]
+fn test_link_section() {
+    assert_eq!(call_interface!(BootIf::boot_stage()), 1);
+    assert_eq!(call_interface!(BootIf::boot_console()), "ttyS0");
+}
+
+#[test]
+fn test_signature_grammar() {
+    let byte = 42u8;
+    assert_eq!(
+        unsafe { call_interface!(SignatureIf::read_byte(&byte)) },
+        42
+    );
+    assert_eq!(unsafe { read_byte(&byte) }, 42);
+    assert_eq!(call_interface!(SignatureIf::c_add(1, 2)), 3);
+    assert_eq!(c_add(3, 4), 7);
+    assert_eq!(
+        call_interface!(SignatureIf::longer("hello", "world!")),
+        "world!"
+    );
+    assert_eq!(longer("foo", "ba"), "foo");
+    assert_eq!(call_interface!(SignatureIf::count_down(3, 0)), 3);
+    assert_eq!(count_down(5, 0), 5);
+}
+
+#[test]
+fn test_cfg_methods() {
+    assert_eq!(call_interface!(CfgIf::word_bits()) as u32, usize::BITS);
+    assert_eq!(word_bits() as u32, usize::BITS);
+}
+
+#[test]
+fn test_associated_types() {
+    assert_eq!(call_interface!(BusIf::read_word(4)), Ok(4));
+    assert_eq!(read_word(3), Err(BusError(3)));
+    assert_eq!(call_interface!(BusIf::write_word(2, 0xbeef)), Ok(()));
+    assert_eq!(write_word(1, 0xbeef), Err(BusError(1)));
+}
+
+#[test]
+fn test_opaque_types() {
+    let mut task = call_interface!(TaskIf::task_spawn(42));
+    assert_eq!(task_id(&task), 42);
+    task_rename(&mut task, "worker");
+    assert_eq!(call_interface!(TaskIf::task_id(&task)), 42);
+    assert_eq!(task_join(task), "worker");
+
+    let mut ticket = call_interface!(TicketIf::ticket_issue(40, 2));
+    assert_eq!(call_interface!(TicketIf::ticket_seq(&ticket)), 42);
+    assert_eq!(
+        call_interface!(TicketIf::ticket_advance(&mut ticket, 8)),
+        50
+    );
+    assert_eq!(call_interface!(TicketIf::ticket_seq(&ticket)), 50);
+}
+
+#[test]
+fn test_instance_methods() {
+    assert_eq!(call_interface!(ConsoleIf::console_write(b"hello")), 5);
+    assert_eq!(console_write(b"!"), 1);
+    assert_eq!(call_interface!(ConsoleIf::console_name()), "ttyS0");
+    assert_eq!(console_count(), 6);
+}
+
+#[test]
+fn test_proxy_type() {
+    fn foo_of<T: SimpleIf>() -> u32 {
+        T::foo()
+    }
+
+    fn name_of<T: ConsoleIf>(console: &T) -> &str {
+        console.console_name()
+    }
+
+    assert_eq!(foo_of::<SimpleIfProxy>(), 456);
+    assert_eq!(name_of(&ConsoleIfProxy), "ttyS0");
+    let mut task = TaskIfProxy::task_spawn(7);
+    TaskIfProxy::task_rename(&mut task, "proxy");
+    assert_eq!(<TaskIfProxy as TaskIf>::task_join(task), "proxy");
+    assert_eq!(<BusIfProxy as BusIf>::read_word(2), Ok(2));
+}
+
+#[test]
+fn test_bound_implementation() {
+    fn baz_of<T: WithCallerIf>(x: i32) -> i32 {
+        T::baz(x)
+    }
+
+    assert_eq!(call_interface!(bound, WithCallerIf::baz(1)), 2);
+    assert_eq!(
+        baz_of::<WithCallerIfBound>(2),
+        baz_of::<WithCallerIfProxy>(2)
+    );
+    assert_eq!(call_interface!(bound, NetIf::mtu()), 1500);
+    // Methods of the supertrait interfaces are bound and called through them.
+    assert_eq!(call_interface!(bound, device::DeviceIf::device_id()), 7);
+    assert_eq!(
+        call_interface!(namespace = A_NS, bound, a::NamespaceIf::qux()),
+        1
+    );
+}
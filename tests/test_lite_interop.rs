@@ -0,0 +1,127 @@
+//! Test that interfaces defined with `crate_interface_lite` can be implemented
+//! with `crate_interface`, and the other way round.
+
+use crate_interface::{call_interface, def_interface, impl_interface};
+
+crate_interface_lite::def_interface!(
+    trait LiteDefinedIf {
+        fn lite_defined(a: u32) -> u32;
+    }
+);
+
+crate_interface_lite::def_interface!(
+    namespace = InteropNs,
+    trait LiteNamespacedIf {
+        fn lite_namespaced() -> &'static str;
+    }
+);
+
+#[def_interface]
+#[allow(dead_code)]
+trait ProcDefinedIf {
+    fn proc_defined(a: u32) -> u32;
+}
+
+#[def_interface(namespace = InteropNs)]
+#[allow(dead_code)]
+trait ProcNamespacedIf {
+    fn proc_namespaced() -> &'static str;
+}
+
+struct ProcImpl;
+
+#[impl_interface]
+impl LiteDefinedIf for ProcImpl {
+    fn lite_defined(a: u32) -> u32 {
+        a + 1
+    }
+}
+
+#[impl_interface(namespace = InteropNs)]
+impl LiteNamespacedIf for ProcImpl {
+    fn lite_namespaced() -> &'static str {
+        "lite namespaced"
+    }
+}
+
+pub struct LiteImpl;
+
+crate_interface_lite::impl_interface!(
+    impl ProcDefinedIf for LiteImpl {
+        fn proc_defined(a: u32) -> u32 {
+            a * 2
+        }
+    }
+);
+
+crate_interface_lite::impl_interface!(
+    namespace = InteropNs,
+    impl ProcNamespacedIf for LiteImpl {
+        fn proc_namespaced() -> &'static str {
+            "proc namespaced"
+        }
+    }
+);
+
+// Namespaces named like a method, as the guards have reserved names.
+crate_interface_lite::def_interface!(
+    namespace = boot,
+    trait LiteBootIf {
+        fn boot() -> u32;
+    }
+);
+
+#[def_interface(namespace = init)]
+#[allow(dead_code)]
+trait ProcInitIf {
+    fn init() -> u32;
+}
+
+#[impl_interface(namespace = boot)]
+impl LiteBootIf for ProcImpl {
+    fn boot() -> u32 {
+        1
+    }
+}
+
+crate_interface_lite::impl_interface!(
+    namespace = init,
+    impl ProcInitIf for LiteImpl {
+        fn init() -> u32 {
+            2
+        }
+    }
+);
+
+#[test]
+fn test_lite_defined_interface() {
+    assert_eq!(
+        crate_interface_lite::call_interface!(LiteDefinedIf::lite_defined(1)),
+        2
+    );
+    assert_eq!(
+        crate_interface_lite::call_interface!(
+            namespace = InteropNs,
+            LiteNamespacedIf::lite_namespaced()
+        ),
+        "lite namespaced"
+    );
+}
+
+#[test]
+fn test_proc_defined_interface() {
+    assert_eq!(call_interface!(ProcDefinedIf::proc_defined(3)), 6);
+    assert_eq!(
+        call_interface!(namespace = InteropNs, ProcNamespacedIf::proc_namespaced()),
+        "proc namespaced"
+    );
+}
+
+#[test]
+fn test_namespace_named_like_method() {
+    assert_eq!(
+        crate_interface_lite::call_interface!(namespace = boot, LiteBootIf::boot()),
+        1
+    );
+    assert_eq!(call_interface!(namespace = init, ProcInitIf::init()), 2);
+}
@@ -0,0 +1,67 @@
+//! Test the `lower_impl_trait` option of `def_interface` and `impl_interface`.
+//!
+//! Lowering returned `impl Trait` requires the `alloc` feature to be enabled.
+//! Run with: cargo test --features alloc --test test_lower_impl_trait
+
+use std::cell::Cell;
+
+use crate_interface::*;
+
+#[def_interface(gen_caller, lower_impl_trait)]
+trait VisitIf {
+    fn visit_all(visitor: impl Fn(u32));
+    fn count(n: u32, counter: impl Fn() + Send) -> u32;
+}
+
+struct VisitIfImpl;
+
+#[impl_interface(lower_impl_trait)]
+impl VisitIf for VisitIfImpl {
+    fn visit_all(visitor: impl Fn(u32)) {
+        for i in 0..3 {
+            visitor(i);
+        }
+    }
+
+    fn count(n: u32, counter: impl Fn() + Send) -> u32 {
+        (0..n).for_each(|_| counter());
+        n
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[def_interface(gen_caller, lower_impl_trait)]
+trait AdderIf {
+    fn adder(base: u32) -> impl Fn(u32) -> u32;
+}
+
+#[cfg(feature = "alloc")]
+struct AdderIfImpl;
+
+#[cfg(feature = "alloc")]
+#[impl_interface(lower_impl_trait)]
+impl AdderIf for AdderIfImpl {
+    fn adder(base: u32) -> impl Fn(u32) -> u32 {
+        move |x| base + x
+    }
+}
+
+#[test]
+fn test_lowered_arguments() {
+    let sum = Cell::new(0);
+    call_interface!(VisitIf::visit_all(&|i| sum.set(sum.get() + i)));
+    assert_eq!(sum.get(), 3);
+
+    // The generated callers accept `impl Trait` arguments as they are.
+    visit_all(|i| sum.set(sum.get() + i));
+    assert_eq!(sum.get(), 6);
+    assert_eq!(count(4, || {}), 4);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_lowered_return_type() {
+    let add = call_interface!(AdderIf::adder(10));
+    assert_eq!(add(5), 15);
+    assert_eq!(adder(1)(2), 3);
+}
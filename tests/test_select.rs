@@ -0,0 +1,53 @@
+//! Test selecting among candidate implementations with a build key.
+//!
+//! The `board` key is set to `qemu-virt` in `.cargo/config.toml`, so only the
+//! matching candidate exports its symbols.
+
+use crate_interface::*;
+
+#[def_interface(version = 2, select = "board")]
+trait BoardIf {
+    fn board_name() -> &'static str;
+    fn uart_base() -> usize;
+}
+
+struct QemuVirt;
+struct Raspi4;
+
+#[impl_interface(select = "board", value = "qemu-virt", version = 2)]
+impl BoardIf for QemuVirt {
+    fn board_name() -> &'static str {
+        "qemu-virt"
+    }
+
+    fn uart_base() -> usize {
+        0x0900_0000
+    }
+}
+
+// The visibility of a candidate that is not selected does not apply to the
+// symbols of the selected one.
+#[impl_interface(select = "board", value = "raspi4", version = 2, visibility = hidden)]
+impl BoardIf for Raspi4 {
+    fn board_name() -> &'static str {
+        "raspi4"
+    }
+
+    fn uart_base() -> usize {
+        0xfe20_1000
+    }
+}
+
+// Bound to a candidate that is not selected, to tell the calls dispatched to
+// the bound type from the ones through the symbols.
+bind!(BoardIf => Raspi4);
+
+#[test]
+fn test_selected_implementation() {
+    assert_eq!(call_interface!(BoardIf::board_name()), "qemu-virt");
+    assert_eq!(call_interface!(BoardIf::uart_base()), 0x0900_0000);
+    assert_eq!(call_interface!(BoardIf::interface_version()), 2);
+    // The other candidates are still plain implementations of the trait.
+    assert_eq!(Raspi4::board_name(), "raspi4");
+    assert_eq!(call_interface!(bound, BoardIf::board_name()), "raspi4");
+}
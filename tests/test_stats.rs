@@ -0,0 +1,75 @@
+//! Test the `stats` option of `impl_interface`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate_interface::*;
+
+static CLOCK: AtomicU64 = AtomicU64::new(0);
+
+/// The clock interface, defined and implemented by the application.
+#[def_interface]
+trait InterfaceClockIf {
+    fn now_nanos() -> u64;
+}
+
+struct ClockImpl;
+
+#[impl_interface]
+impl InterfaceClockIf for ClockImpl {
+    fn now_nanos() -> u64 {
+        // Every reading advances the clock by 10 nanoseconds.
+        CLOCK.fetch_add(10, Ordering::Relaxed)
+    }
+}
+
+#[def_interface]
+trait IrqIf {
+    fn irq_handler(irq: usize) -> bool;
+    fn irq_count() -> usize;
+}
+
+struct IrqIfImpl;
+
+#[impl_interface(stats)]
+impl IrqIf for IrqIfImpl {
+    fn irq_handler(irq: usize) -> bool {
+        irq < 32
+    }
+
+    fn irq_count() -> usize {
+        32
+    }
+}
+
+/// A second interface with statistics implemented by the same type.
+#[def_interface]
+trait TimerIf {
+    fn timer_tick();
+}
+
+#[impl_interface(stats)]
+impl TimerIf for IrqIfImpl {
+    fn timer_tick() {}
+}
+
+#[test]
+fn test_call_stats() {
+    for irq in 0..3 {
+        assert!(call_interface!(IrqIf::irq_handler(irq)));
+    }
+    // Calling the implementation directly is not an interface call.
+    assert_eq!(IrqIfImpl::irq_count(), 32);
+
+    call_interface!(TimerIf::timer_tick());
+
+    let mut stats: Vec<_> = interface_stats!().collect();
+    stats.sort();
+    assert_eq!(
+        stats,
+        [
+            ("IrqIf", "irq_count", 0, 0),
+            ("IrqIf", "irq_handler", 3, 30),
+            ("TimerIf", "timer_tick", 1, 10)
+        ]
+    );
+}
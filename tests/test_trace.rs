@@ -0,0 +1,61 @@
+//! Test the `trace` option of `def_interface`.
+
+use std::sync::Mutex;
+
+use crate_interface::*;
+
+static TRACE_LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// The tracing hook interface, defined and implemented by the application.
+#[def_interface]
+trait InterfaceTraceIf {
+    fn on_enter(trait_name: &'static str, method: &'static str, namespace: &'static str);
+    fn on_exit(trait_name: &'static str, method: &'static str, namespace: &'static str);
+}
+
+struct TraceImpl;
+
+#[impl_interface]
+impl InterfaceTraceIf for TraceImpl {
+    fn on_enter(trait_name: &'static str, method: &'static str, namespace: &'static str) {
+        let entry = format!("enter {}:{}::{}", namespace, trait_name, method);
+        TRACE_LOG.lock().unwrap().push(entry);
+    }
+
+    fn on_exit(trait_name: &'static str, method: &'static str, namespace: &'static str) {
+        let entry = format!("exit {}:{}::{}", namespace, trait_name, method);
+        TRACE_LOG.lock().unwrap().push(entry);
+    }
+}
+
+#[def_interface(trace, gen_caller, namespace = TraceNs)]
+trait TracedIf {
+    fn double(x: u32) -> u32;
+}
+
+struct TracedIfImpl;
+
+#[impl_interface(namespace = TraceNs)]
+impl TracedIf for TracedIfImpl {
+    fn double(x: u32) -> u32 {
+        TRACE_LOG.lock().unwrap().push(format!("double {}", x));
+        x * 2
+    }
+}
+
+#[test]
+fn test_trace_hooks() {
+    assert_eq!(call_interface!(namespace = TraceNs, TracedIf::double(3)), 6);
+    assert_eq!(double(4), 8);
+    assert_eq!(
+        *TRACE_LOG.lock().unwrap(),
+        [
+            "enter TraceNs:TracedIf::double",
+            "double 3",
+            "exit TraceNs:TracedIf::double",
+            "enter TraceNs:TracedIf::double",
+            "double 4",
+            "exit TraceNs:TracedIf::double",
+        ]
+    );
+}
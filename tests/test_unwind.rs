@@ -0,0 +1,97 @@
+//! Test the `unwind` option of `def_interface` and `impl_interface`.
+
+use std::panic;
+
+use crate_interface::*;
+
+/// The panic hook interface, defined and implemented by the application.
+#[def_interface]
+trait InterfacePanicIf {
+    fn on_panic(trait_name: &'static str, method: &'static str, message: &str) -> !;
+}
+
+struct PanicImpl;
+
+#[impl_interface]
+impl InterfacePanicIf for PanicImpl {
+    fn on_panic(trait_name: &'static str, method: &'static str, message: &str) -> ! {
+        panic!("hooked {}::{}: {}", trait_name, method, message);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    Invalid,
+    Panicked(String),
+}
+
+impl From<ParserIfPanic> for ParseError {
+    fn from(panic: ParserIfPanic) -> Self {
+        ParseError::Panicked(panic.to_string())
+    }
+}
+
+#[def_interface(unwind = catch, gen_caller)]
+trait ParserIf {
+    fn parse(input: &str) -> Result<u32, ParseError>;
+    fn checked_parse(input: &str) -> u32;
+}
+
+struct ParserImpl;
+
+#[impl_interface(unwind = catch)]
+impl ParserIf for ParserImpl {
+    fn parse(input: &str) -> Result<u32, ParseError> {
+        if input == "boom" {
+            panic!("cannot parse {}", input);
+        }
+        input.parse().map_err(|_| ParseError::Invalid)
+    }
+
+    fn checked_parse(input: &str) -> u32 {
+        input.parse().expect("not a number")
+    }
+}
+
+#[def_interface(unwind = abort)]
+trait CounterIf {
+    fn next(current: u32) -> u32;
+}
+
+struct CounterImpl;
+
+#[impl_interface(unwind = abort)]
+impl CounterIf for CounterImpl {
+    fn next(current: u32) -> u32 {
+        current + 1
+    }
+}
+
+#[test]
+fn test_catch_into_result() {
+    assert_eq!(call_interface!(ParserIf::parse("42")), Ok(42));
+    assert_eq!(parse("x"), Err(ParseError::Invalid));
+    assert_eq!(
+        call_interface!(ParserIf::parse("boom")),
+        Err(ParseError::Panicked(
+            "`ParserIf::parse` panicked: cannot parse boom".into()
+        ))
+    );
+}
+
+#[test]
+fn test_catch_into_hook() {
+    assert_eq!(checked_parse("7"), 7);
+    let payload =
+        panic::catch_unwind(|| call_interface!(ParserIf::checked_parse("seven"))).unwrap_err();
+    assert_eq!(
+        payload.downcast_ref::<String>().unwrap(),
+        "hooked ParserIf::checked_parse: not a number: ParseIntError { kind: InvalidDigit }"
+    );
+}
+
+#[test]
+fn test_abort_without_panic() {
+    assert_eq!(call_interface!(CounterIf::next(1)), 2);
+    assert_eq!(CounterIfProxy::next(2), 3);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "weak_default")]
+#![cfg_attr(feature = "weak_default", feature(linkage))]
+
+//! Test interface versioning.
+//!
+//! Methods added after the first version are filled with weak defaults for the
+//! implementations of older versions, so this test requires nightly Rust and
+//! the `weak_default` feature to be enabled.
+//! Run with: cargo +nightly test --features weak_default --test test_version
+
+use crate_interface::*;
+
+#[def_interface(version = 3)]
+trait VersionedIf {
+    fn base() -> u32;
+
+    /// Added in version 2 with a default implementation.
+    #[since(2)]
+    fn with_default(x: u32) -> u32 {
+        Self::base() + x
+    }
+
+    /// Added in version 3 without a default implementation.
+    #[since(3)]
+    fn unsupported(x: u32) -> u32;
+}
+
+struct OldImpl;
+
+#[impl_interface(version = 1)]
+impl VersionedIf for OldImpl {
+    fn base() -> u32 {
+        10
+    }
+}
+
+#[test]
+fn test_implemented_version() {
+    assert_eq!(call_interface!(VersionedIf::interface_version()), 1);
+    assert_eq!(call_interface!(VersionedIf::base()), 10);
+}
+
+#[test]
+fn test_newer_method_with_default() {
+    assert_eq!(call_interface!(VersionedIf::with_default(5)), 15);
+}
+
+#[test]
+#[should_panic(expected = "`VersionedIf::unsupported` (since version 3) is not supported")]
+fn test_newer_method_unsupported() {
+    call_interface!(VersionedIf::unsupported(1));
+}
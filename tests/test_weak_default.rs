@@ -29,6 +29,14 @@ trait DefaultMethodIf {
         a + b
     }
 
+    /// Unsafe method with default implementation, lifetimes and `mut` bindings.
+    unsafe fn default_unsafe<'a>(mut skip: usize, s: &'a str, _t: &'a str) -> &'a str {
+        while skip > 1 {
+            skip -= 1;
+        }
+        &s[skip..]
+    }
+
     /// Method without default implementation - must be implemented.
     fn required_method() -> u32;
 }
@@ -56,4 +64,206 @@ fn test_weak_default_methods() {
         call_interface!(DefaultMethodIf::default_with_args, 10, 20),
         30
     );
+    assert_eq!(
+        unsafe { call_interface!(DefaultMethodIf::default_unsafe(3, "abc", "")) },
+        "bc"
+    );
+}
+
+/// A trait with a method retired in favor of a new one.
+#[def_interface(gen_caller)]
+trait MigrationIf {
+    /// Old method forwarding to the new one, which implementors may skip.
+    #[deprecated(note = "use `checksum` instead")]
+    #[interface(replaced_by = checksum)]
+    fn sum(data: &[u8]) -> u32;
+
+    /// Old unsafe method forwarding to the new one.
+    #[deprecated]
+    #[interface(replaced_by = checksum_unchecked)]
+    unsafe fn sum_unchecked(data: *const u8, mut len: usize) -> u32;
+
+    fn checksum(data: &[u8]) -> u32;
+
+    unsafe fn checksum_unchecked(data: *const u8, len: usize) -> u32;
+}
+
+struct NewImpl;
+
+#[impl_interface]
+impl MigrationIf for NewImpl {
+    fn checksum(data: &[u8]) -> u32 {
+        data.iter().map(|&b| b as u32).sum()
+    }
+
+    unsafe fn checksum_unchecked(data: *const u8, len: usize) -> u32 {
+        Self::checksum(unsafe { core::slice::from_raw_parts(data, len) })
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_replaced_methods() {
+    let data = [1, 2, 3];
+    assert_eq!(call_interface!(MigrationIf::sum(&data)), 6);
+    assert_eq!(sum(&data[1..]), 5);
+    assert_eq!(
+        unsafe { call_interface!(MigrationIf::sum_unchecked(data.as_ptr(), 2)) },
+        3
+    );
+    assert_eq!(unsafe { sum_unchecked(data.as_ptr(), 1) }, 1);
+    assert_eq!(checksum(&data), 6);
+}
+
+/// A trait whose default implementation calls platform-conditional methods.
+#[def_interface]
+trait PlatformIf {
+    #[cfg(target_endian = "little")]
+    fn endian_tag() -> &'static str;
+    #[cfg(target_endian = "big")]
+    fn endian_tag() -> &'static str;
+    #[cfg(any())]
+    fn never() -> &'static str;
+
+    fn describe() -> &'static str {
+        #[cfg(any())]
+        return Self::never();
+        Self::endian_tag()
+    }
+}
+
+struct PlatformImpl;
+
+#[impl_interface]
+impl PlatformIf for PlatformImpl {
+    #[cfg(target_endian = "little")]
+    fn endian_tag() -> &'static str {
+        "le"
+    }
+    #[cfg(target_endian = "big")]
+    fn endian_tag() -> &'static str {
+        "be"
+    }
+}
+
+#[test]
+fn test_cfg_methods_in_default() {
+    let expected = if cfg!(target_endian = "little") {
+        "le"
+    } else {
+        "be"
+    };
+    assert_eq!(call_interface!(PlatformIf::describe()), expected);
+}
+
+/// A trait whose weak defaults are placed in linker sections, with hidden
+/// symbols.
+#[def_interface(link_section = ".text.boot", visibility = hidden)]
+trait EarlyIf {
+    fn early_id() -> u32;
+
+    fn early_stage() -> u32 {
+        Self::early_id() + 1
+    }
+
+    #[interface(link_section = ".text.boot.name", visibility = protected)]
+    fn early_name() -> &'static str {
+        "early"
+    }
+}
+
+struct EarlyImpl;
+
+#[impl_interface(link_section = ".text.boot")]
+impl EarlyIf for EarlyImpl {
+    fn early_id() -> u32 {
+        1
+    }
+}
+
+#[test]
+fn test_link_section_of_defaults() {
+    assert_eq!(call_interface!(EarlyIf::early_stage()), 2);
+    assert_eq!(call_interface!(EarlyIf::early_name()), "early");
+}
+
+/// A trait whose panicking default implementation is caught into an error.
+#[def_interface(unwind = catch)]
+trait ProbeIf {
+    fn probe_count() -> Result<usize, String>;
+
+    fn probe(index: usize) -> Result<u32, String> {
+        let ids = [10, 20];
+        Ok(ids[index] + Self::probe_count()? as u32)
+    }
+}
+
+impl From<ProbeIfPanic> for String {
+    fn from(panic: ProbeIfPanic) -> Self {
+        panic.method.to_string()
+    }
+}
+
+struct ProbeImpl;
+
+#[impl_interface(unwind = catch)]
+impl ProbeIf for ProbeImpl {
+    fn probe_count() -> Result<usize, String> {
+        Ok(2)
+    }
+}
+
+#[test]
+fn test_unwind_of_defaults() {
+    assert_eq!(call_interface!(ProbeIf::probe(1)), Ok(22));
+    assert_eq!(call_interface!(ProbeIf::probe(2)), Err("probe".to_string()));
+}
+
+/// A trait with an opaque type used in default implementations.
+#[def_interface]
+trait CounterIf {
+    type Counter: Sized = opaque!(size = 16, align = 8);
+
+    fn counter_new(start: u32) -> Self::Counter;
+    fn counter_get(counter: &Self::Counter) -> u32;
+
+    fn counter_default() -> Self::Counter {
+        Self::counter_new(100)
+    }
+
+    fn counter_recreate(counter: Self::Counter) -> Self::Counter {
+        let value = call_interface!(CounterIf::counter_get(&counter));
+        Self::counter_new(value + 1)
+    }
+}
+
+struct CounterImpl;
+
+#[impl_interface]
+impl CounterIf for CounterImpl {
+    type Counter = u64;
+
+    fn counter_new(start: u32) -> Self::Counter {
+        start as u64
+    }
+
+    fn counter_get(counter: &Self::Counter) -> u32 {
+        *counter as u32
+    }
+}
+
+#[test]
+fn test_opaque_types_in_default() {
+    let counter = call_interface!(CounterIf::counter_default());
+    assert_eq!(call_interface!(CounterIf::counter_get(&counter)), 100);
+    let counter = call_interface!(CounterIf::counter_recreate(counter));
+    assert_eq!(call_interface!(CounterIf::counter_get(&counter)), 101);
+    assert_eq!(<CounterImpl as CounterIf>::counter_recreate(7), 8);
+}
+
+#[test]
+fn test_proxy_type_with_defaults() {
+    assert_eq!(DefaultMethodIfProxy::default_method(), 42);
+    assert_eq!(DefaultMethodIfProxy::required_method(), 100);
+    assert_eq!(<MigrationIfProxy as MigrationIf>::checksum(&[1, 2]), 3);
 }